@@ -43,23 +43,20 @@ async fn main() -> anyhow::Result<()> {
     let opt = Opt::parse();
     let listener = TcpListener::bind(opt.listen).await?;
     eprintln!("Listening on http://{}", listener.local_addr()?);
-    let assets = axum_embed::ServeEmbed::<Assets>::with_parameters(
-        if opt.fallback {
-            Some("404.html".to_owned())
-        } else {
-            None
-        },
+    let mut builder = axum_embed::ServeEmbed::<Assets>::builder().fallback_behavior(
         match opt.fallback_behavior {
             FallbackBehavior::Ok => axum_embed::FallbackBehavior::Ok,
             FallbackBehavior::Redirect => axum_embed::FallbackBehavior::Redirect,
             FallbackBehavior::NotFound => axum_embed::FallbackBehavior::NotFound,
         },
-        if opt.no_index {
-            None
-        } else {
-            Some("index.html".to_owned())
-        },
     );
+    if opt.fallback {
+        builder = builder.fallback_file("404.html");
+    }
+    if opt.no_index {
+        builder = builder.no_index_file();
+    }
+    let assets = builder.build();
     let app = axum::Router::new().nest_service("/", assets);
     axum::serve(listener, app).await?;
 
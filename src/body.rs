@@ -0,0 +1,67 @@
+use std::{convert::Infallible, pin::Pin, task::Poll};
+
+use bytes::Bytes;
+use http_body::{Body, Frame, SizeHint};
+use http_body_util::Full;
+
+/// [`ServeBody`] is the response body of [`ServeEmbed`](crate::ServeEmbed).
+///
+/// Most responses have the whole content in memory. Some responses, such as the live reload event stream, are streamed.
+#[derive(Debug)]
+pub struct ServeBody {
+    inner: BodyInner,
+}
+
+#[derive(Debug)]
+enum BodyInner {
+    Full(Full<Bytes>),
+    #[cfg(feature = "dev")]
+    LiveReload(crate::live_reload::LiveReloadEvents),
+}
+
+impl ServeBody {
+    pub(crate) fn full(data: impl Into<Bytes>) -> Self {
+        Self {
+            inner: BodyInner::Full(Full::new(data.into())),
+        }
+    }
+
+    #[cfg(feature = "dev")]
+    pub(crate) fn live_reload(events: crate::live_reload::LiveReloadEvents) -> Self {
+        Self {
+            inner: BodyInner::LiveReload(events),
+        }
+    }
+}
+
+impl Body for ServeBody {
+    type Data = Bytes;
+    type Error = Infallible;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        match &mut self.get_mut().inner {
+            BodyInner::Full(body) => Pin::new(body).poll_frame(cx),
+            #[cfg(feature = "dev")]
+            BodyInner::LiveReload(events) => Pin::new(events).poll_frame(cx),
+        }
+    }
+
+    fn is_end_stream(&self) -> bool {
+        match &self.inner {
+            BodyInner::Full(body) => body.is_end_stream(),
+            #[cfg(feature = "dev")]
+            BodyInner::LiveReload(events) => events.is_end_stream(),
+        }
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        match &self.inner {
+            BodyInner::Full(body) => body.size_hint(),
+            #[cfg(feature = "dev")]
+            BodyInner::LiveReload(events) => events.size_hint(),
+        }
+    }
+}
@@ -0,0 +1,155 @@
+use http::{header, HeaderMap, HeaderName, HeaderValue};
+
+use crate::Glob;
+
+/// [`CorsRule`] allows cross-origin requests for the files that match a glob, such as fonts and JavaScript modules.
+///
+/// Rules are added with [`ServeEmbedBuilder::cors_rule`](crate::ServeEmbedBuilder::cors_rule) and evaluated in the order they were added.
+/// The first rule that matches the normalized path of the request is used. Paths that match no rule get no CORS headers.
+///
+/// A matching response gets `Vary: Origin`, and `Access-Control-Allow-Origin` if the `Origin` of the request is allowed.
+/// A preflight request, which is an `OPTIONS` request with `Access-Control-Request-Method`, is answered with `204 No Content`,
+/// the accepted methods and the allowed request headers.
+///
+/// # Example
+/// ```ignore
+/// let serve_assets = ServeEmbed::<Assets>::builder()
+///     .cors_rule(CorsRule::any_origin("fonts/**"))
+///     .cors_rule(
+///         CorsRule::origins("modules/**", ["https://app.example.com"])
+///             .allow_credentials(true)
+///             .max_age(3600),
+///     )
+///     .build();
+/// ```
+#[derive(Debug, Clone)]
+pub struct CorsRule {
+    glob: Glob,
+    origins: Option<Vec<String>>,
+    credentials: bool,
+    allow_headers: Vec<HeaderName>,
+    expose_headers: Vec<HeaderName>,
+    max_age: Option<u32>,
+}
+
+impl CorsRule {
+    /// Creates a rule that allows any origin for the paths that match `pattern`. Please see [`Glob`] for the syntax.
+    pub fn any_origin(pattern: impl Into<Glob>) -> Self {
+        Self {
+            glob: pattern.into(),
+            origins: None,
+            credentials: false,
+            allow_headers: Vec::new(),
+            expose_headers: Vec::new(),
+            max_age: None,
+        }
+    }
+
+    /// Creates a rule that allows `origins`, such as `"https://app.example.com"`, for the paths that match `pattern`.
+    ///
+    /// Origins are compared case-insensitively.
+    pub fn origins(
+        pattern: impl Into<Glob>,
+        origins: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        Self {
+            origins: Some(origins.into_iter().map(Into::into).collect()),
+            ..Self::any_origin(pattern)
+        }
+    }
+
+    /// Allows requests with credentials, such as cookies. The default is `false`.
+    ///
+    /// The origin of the request is echoed back instead of `*`, because browsers reject `*` for requests with credentials.
+    /// Credentials are allowed only for a rule created by [`CorsRule::origins`]. A rule created by [`CorsRule::any_origin`]
+    /// keeps sending `*` without `Access-Control-Allow-Credentials`, because echoing any origin with credentials
+    /// would let every website read the files with the cookies of the user.
+    pub fn allow_credentials(mut self, enabled: bool) -> Self {
+        self.credentials = enabled;
+        self
+    }
+
+    /// Allows a request header in preflight requests, such as `Range`.
+    pub fn allow_header(mut self, name: HeaderName) -> Self {
+        self.allow_headers.push(name);
+        self
+    }
+
+    /// Exposes a response header to scripts, such as `ETag`.
+    pub fn expose_header(mut self, name: HeaderName) -> Self {
+        self.expose_headers.push(name);
+        self
+    }
+
+    /// Lets browsers cache the result of a preflight request for `seconds`.
+    pub fn max_age(mut self, seconds: u32) -> Self {
+        self.max_age = Some(seconds);
+        self
+    }
+
+    /// Returns `true` if this rule applies to `path`, a normalized path relative to the root.
+    pub(crate) fn is_match(&self, path: &str) -> bool {
+        self.glob.is_match(path)
+    }
+
+    /// Returns the value of `Access-Control-Allow-Origin` for `origin`, or `None` if `origin` is not allowed.
+    fn allow_origin(&self, origin: &HeaderValue) -> Option<HeaderValue> {
+        match &self.origins {
+            None => Some(HeaderValue::from_static("*")),
+            Some(origins) => {
+                let origin_str = origin.to_str().ok()?;
+                origins
+                    .iter()
+                    .any(|x| x.eq_ignore_ascii_case(origin_str))
+                    .then(|| origin.clone())
+            }
+        }
+    }
+
+    /// Adds the CORS headers of a response to a request from `origin`.
+    pub(crate) fn apply(&self, origin: Option<&HeaderValue>, headers: &mut HeaderMap) {
+        headers.append(header::VARY, HeaderValue::from_static("Origin"));
+        let Some(allow_origin) = origin.and_then(|x| self.allow_origin(x)) else {
+            return;
+        };
+        headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, allow_origin);
+        if self.credentials && self.origins.is_some() {
+            headers.insert(
+                header::ACCESS_CONTROL_ALLOW_CREDENTIALS,
+                HeaderValue::from_static("true"),
+            );
+        }
+        if let Some(value) = join(&self.expose_headers) {
+            headers.insert(header::ACCESS_CONTROL_EXPOSE_HEADERS, value);
+        }
+    }
+
+    /// Adds the headers of a response to a preflight request from `origin`. `allow` is the list of the accepted methods.
+    ///
+    /// The headers added by [`CorsRule::apply`] are not included.
+    pub(crate) fn apply_preflight(
+        &self,
+        origin: &HeaderValue,
+        allow: HeaderValue,
+        headers: &mut HeaderMap,
+    ) {
+        if self.allow_origin(origin).is_none() {
+            return;
+        }
+        headers.insert(header::ACCESS_CONTROL_ALLOW_METHODS, allow);
+        if let Some(value) = join(&self.allow_headers) {
+            headers.insert(header::ACCESS_CONTROL_ALLOW_HEADERS, value);
+        }
+        if let Some(max_age) = self.max_age {
+            headers.insert(header::ACCESS_CONTROL_MAX_AGE, max_age.into());
+        }
+    }
+}
+
+fn join(names: &[HeaderName]) -> Option<HeaderValue> {
+    if names.is_empty() {
+        return None;
+    }
+    let names: Vec<&str> = names.iter().map(|x| x.as_str()).collect();
+    HeaderValue::from_str(&names.join(", ")).ok()
+}
@@ -0,0 +1,102 @@
+use std::{
+    borrow::Cow,
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+use crate::{Asset, AssetSource};
+
+/// [`DirSource`] is an [`AssetSource`] that reads files from a folder on disk for every request.
+///
+/// Files are read when they are requested, so edits show up without recompiling.
+/// The `ETag` is computed from the content of the file, in the same way as an embedded file.
+/// This source is used by [`ServeEmbedBuilder::override_dir`](crate::ServeEmbedBuilder::override_dir)
+/// and by the development mode of the `dev` feature.
+///
+/// Requested paths are confined to the folder. Paths containing `..`, empty segments, backslashes or drive prefixes are rejected,
+/// and a symbolic link that points outside of the folder is treated as a missing file.
+#[derive(Debug, Clone)]
+pub struct DirSource {
+    root: PathBuf,
+}
+
+impl DirSource {
+    /// Creates a new `DirSource` that serves files in `root`.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// Returns the folder this source serves files from.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Resolves `path` to a file path inside the root folder, or `None` if the path is invalid or escapes the folder.
+    fn resolve(&self, path: &str) -> Option<PathBuf> {
+        let mut file_path = self.root.clone();
+        for segment in path.split('/') {
+            if segment.is_empty()
+                || segment == "."
+                || segment == ".."
+                || segment.contains(['\\', ':', '\0'])
+            {
+                return None;
+            }
+            file_path.push(segment);
+        }
+
+        let root = self.root.canonicalize().ok()?;
+        let file_path = file_path.canonicalize().ok()?;
+        if file_path.starts_with(&root) && file_path.is_file() {
+            Some(file_path)
+        } else {
+            None
+        }
+    }
+}
+
+impl AssetSource for DirSource {
+    fn get(&self, path: &str) -> Option<Asset> {
+        let file_path = self.resolve(path)?;
+        let data = std::fs::read(&file_path).ok()?;
+        let asset = Asset::new(data);
+        match modified_time(&file_path) {
+            Some(last_modified) => Some(asset.with_last_modified(last_modified)),
+            None => Some(asset),
+        }
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = Cow<'static, str>> + '_> {
+        let mut paths = Vec::new();
+        walk(&self.root, "", &mut paths);
+        Box::new(paths.into_iter().map(Cow::Owned))
+    }
+}
+
+fn modified_time(path: &Path) -> Option<u64> {
+    std::fs::metadata(path)
+        .and_then(|x| x.modified())
+        .ok()
+        .and_then(|x| x.duration_since(UNIX_EPOCH).ok())
+        .map(|x| x.as_secs())
+}
+
+/// Collects the relative paths of all files under `folder`, joined with `/`.
+pub(crate) fn walk(folder: &Path, prefix: &str, paths: &mut Vec<String>) {
+    let Ok(entries) = std::fs::read_dir(folder) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let Ok(name) = entry.file_name().into_string() else {
+            continue;
+        };
+        let relative_path = format!("{}{}", prefix, name);
+        match entry.file_type() {
+            Ok(file_type) if file_type.is_dir() => {
+                walk(&entry.path(), &format!("{}/", relative_path), paths);
+            }
+            Ok(_) => paths.push(relative_path),
+            Err(_) => {}
+        }
+    }
+}
@@ -0,0 +1,246 @@
+use bytes::Bytes;
+use http::StatusCode;
+use rust_embed::RustEmbed;
+
+#[derive(Clone, RustEmbed)]
+#[folder = "src/assets"]
+struct DefaultFallback;
+
+/// [`ErrorFormat`] is an enumeration of the formats of an error response body.
+///
+/// The format is chosen by the `Accept` header of the request. If the client accepts several formats equally,
+/// HTML is used for `404 Not Found` and plain text is used for `405 Method Not Allowed` and redirects.
+/// Please see [`ServeEmbedBuilder::error_page`](crate::ServeEmbedBuilder::error_page) to register a page for a format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorFormat {
+    /// An HTML page (`text/html`) for browsers.
+    Html,
+    /// An RFC 9457 problem details object (`application/problem+json`) for API clients. `application/json` is also accepted.
+    ProblemJson,
+    /// Plain text (`text/plain`).
+    Text,
+}
+
+impl ErrorFormat {
+    pub(crate) const ALL: &'static [ErrorFormat] = &[
+        ErrorFormat::Html,
+        ErrorFormat::ProblemJson,
+        ErrorFormat::Text,
+    ];
+
+    /// Returns the `Content-Type` of this format, such as `"application/problem+json"`.
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Html => "text/html",
+            Self::ProblemJson => "application/problem+json",
+            Self::Text => "text/plain",
+        }
+    }
+
+    fn media_types(self) -> &'static [&'static str] {
+        match self {
+            Self::Html => &["text/html"],
+            Self::ProblemJson => &["application/problem+json", "application/json"],
+            Self::Text => &["text/plain"],
+        }
+    }
+}
+
+/// Chooses one of `formats` by the `Accept` header of a request.
+///
+/// Each format gets the quality of the most specific media range that matches it. The format with the highest quality wins,
+/// and `default` wins a tie. `default` is also used if there is no `Accept` header or no format is acceptable.
+pub(crate) fn negotiate(
+    accept: Option<&str>,
+    formats: &[ErrorFormat],
+    default: ErrorFormat,
+) -> ErrorFormat {
+    let Some(accept) = accept else {
+        return default;
+    };
+    let ranges: Vec<(&str, f32)> = accept
+        .split(',')
+        .filter_map(|range| {
+            let mut params = range.split(';');
+            let media_type = params.next()?.trim();
+            let quality = params
+                .filter_map(|x| x.trim().strip_prefix("q="))
+                .find_map(|x| x.trim().parse().ok())
+                .unwrap_or(1.0);
+            Some((media_type, quality))
+        })
+        .collect();
+
+    let quality = |format: ErrorFormat| {
+        format
+            .media_types()
+            .iter()
+            .filter_map(|media_type| {
+                ranges
+                    .iter()
+                    .filter_map(|(range, quality)| {
+                        let specificity = if range.eq_ignore_ascii_case(media_type) {
+                            2
+                        } else if range
+                            .strip_suffix("/*")
+                            .is_some_and(|x| media_type.split('/').next() == Some(x))
+                        {
+                            1
+                        } else if *range == "*/*" {
+                            0
+                        } else {
+                            return None;
+                        };
+                        Some((specificity, *quality))
+                    })
+                    .max_by_key(|(specificity, _)| *specificity)
+                    .map(|(_, quality)| quality)
+            })
+            .fold(0.0, f32::max)
+    };
+
+    let mut best = (default, quality(default));
+    for format in formats {
+        let format_quality = quality(*format);
+        if format_quality > best.1 {
+            best = (*format, format_quality);
+        }
+    }
+    if best.1 > 0.0 {
+        best.0
+    } else {
+        default
+    }
+}
+
+/// Builds the built-in body of an error response or a redirect response.
+///
+/// `request_path` is the path of the request, and `location` is the target of a redirect.
+pub(crate) fn default_body(
+    status: StatusCode,
+    format: ErrorFormat,
+    request_path: &str,
+    location: Option<&str>,
+) -> Bytes {
+    let reason = status.canonical_reason().unwrap_or("Error");
+    let detail = match (status, location) {
+        (_, Some(_)) => "The document has moved.",
+        (StatusCode::NOT_FOUND, None) => "The requested URL was not found on this server.",
+        (StatusCode::METHOD_NOT_ALLOWED, None) => {
+            "The requested method is not allowed for the URL."
+        }
+        _ => reason,
+    };
+
+    match format {
+        ErrorFormat::Html if status == StatusCode::NOT_FOUND => {
+            DefaultFallback::get("404.html").unwrap().data.into_owned().into()
+        }
+        ErrorFormat::Html => {
+            let detail = match location {
+                Some(location) => format!(
+                    "The document has moved <a href=\"{}\">here</a>.",
+                    escape_html(location)
+                ),
+                None => escape_html(detail),
+            };
+            format!(
+                "<!DOCTYPE html>\n<html>\n\n<head>\n    <title>{} {}</title>\n</head>\n\n<body>\n    <h1>{}</h1>\n    <p>{}</p>\n</body>\n\n</html>",
+                status.as_u16(),
+                reason,
+                reason,
+                detail
+            )
+            .into()
+        }
+        ErrorFormat::ProblemJson => format!(
+            "{{\"type\":\"about:blank\",\"title\":\"{}\",\"status\":{},\"detail\":\"{}\",\"instance\":\"{}\"}}",
+            escape_json(reason),
+            status.as_u16(),
+            escape_json(detail),
+            escape_json(request_path)
+        )
+        .into(),
+        ErrorFormat::Text => match status {
+            StatusCode::NOT_FOUND => "Not found".into(),
+            StatusCode::METHOD_NOT_ALLOWED => "Method not allowed".into(),
+            StatusCode::MOVED_PERMANENTLY => "Moved permanently".into(),
+            StatusCode::TEMPORARY_REDIRECT => "Temporary redirect".into(),
+            _ => Bytes::from(reason),
+        },
+    }
+}
+
+/// Escapes `&`, `<`, `>`, `"` and `'` for HTML.
+pub(crate) fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Escapes a string for a JSON string literal.
+pub(crate) fn escape_json(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Returns the escape function for a value inserted into a body of `content_type`.
+pub(crate) fn escape_for(content_type: &str) -> fn(&str) -> String {
+    if content_type.contains("html") || content_type.contains("xml") {
+        escape_html
+    } else if content_type.contains("json") {
+        escape_json
+    } else {
+        str::to_owned
+    }
+}
+
+/// Replaces the placeholders such as `{{path}}` in `template` with the escaped `values`.
+///
+/// Unknown placeholders are kept as they are. `None` is returned if `template` is not UTF-8 or has no known placeholder.
+pub(crate) fn render_template(
+    template: &[u8],
+    escape: fn(&str) -> String,
+    values: &[(&str, String)],
+) -> Option<Bytes> {
+    let mut rest = std::str::from_utf8(template).ok()?;
+    let mut rendered = String::with_capacity(rest.len());
+    let mut replaced = false;
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        let name = rest[start + 2..start + 2 + end].trim();
+        rendered.push_str(&rest[..start]);
+        match values.iter().find(|(x, _)| *x == name) {
+            Some((_, value)) => {
+                rendered.push_str(&escape(value));
+                replaced = true;
+            }
+            None => rendered.push_str(&rest[start..start + 4 + end]),
+        }
+        rest = &rest[start + 4 + end..];
+    }
+    rendered.push_str(rest);
+    replaced.then(|| rendered.into())
+}
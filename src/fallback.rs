@@ -0,0 +1,82 @@
+use http::{HeaderMap, HeaderName, HeaderValue};
+
+use crate::{FallbackBehavior, Glob};
+
+/// [`FallbackRule`] sets the fallback file and the fallback behavior for a part of the path space.
+///
+/// Rules are added with [`ServeEmbedBuilder::fallback_rule`](crate::ServeEmbedBuilder::fallback_rule) and evaluated in the order they were added.
+/// The first rule that matches the normalized path of the request is used when the requested file is not found.
+/// If no rule matches, the global fallback file and fallback behavior are used.
+///
+/// # Example
+/// ```ignore
+/// let serve_assets = ServeEmbed::<Assets>::builder()
+///     .fallback_rule(FallbackRule::prefix("app", "app/index.html", FallbackBehavior::Ok))
+///     .fallback_rule(FallbackRule::prefix("docs", "docs/404.html", FallbackBehavior::NotFound))
+///     .fallback_rule(FallbackRule::prefix("admin", "admin/login", FallbackBehavior::Redirect))
+///     .build();
+/// ```
+#[derive(Debug, Clone)]
+pub struct FallbackRule {
+    matcher: Matcher,
+    pub(crate) file: String,
+    pub(crate) behavior: FallbackBehavior,
+    pub(crate) headers: HeaderMap,
+}
+
+#[derive(Debug, Clone)]
+enum Matcher {
+    Prefix(String),
+    Glob(Glob),
+}
+
+impl FallbackRule {
+    /// Creates a rule for the paths under `prefix`, such as `"docs"` for `/docs` and `/docs/guide/`.
+    ///
+    /// The prefix is matched by whole segments, so `"app"` does not match `/application`.
+    pub fn prefix(
+        prefix: impl AsRef<str>,
+        file: impl Into<String>,
+        behavior: FallbackBehavior,
+    ) -> Self {
+        Self {
+            matcher: Matcher::Prefix(prefix.as_ref().trim_matches('/').to_owned()),
+            file: file.into(),
+            behavior,
+            headers: HeaderMap::new(),
+        }
+    }
+
+    /// Creates a rule for the paths that match `pattern`. Please see [`Glob`] for the syntax.
+    pub fn glob(
+        pattern: impl Into<Glob>,
+        file: impl Into<String>,
+        behavior: FallbackBehavior,
+    ) -> Self {
+        Self {
+            matcher: Matcher::Glob(pattern.into()),
+            file: file.into(),
+            behavior,
+            headers: HeaderMap::new(),
+        }
+    }
+
+    /// Adds a header to the responses that serve the fallback file of this rule, such as `Retry-After` for a maintenance page.
+    pub fn header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.headers.append(name, value);
+        self
+    }
+
+    /// Returns `true` if this rule applies to `path`, a normalized path relative to the root.
+    pub(crate) fn is_match(&self, path: &str) -> bool {
+        match &self.matcher {
+            Matcher::Prefix(prefix) => {
+                prefix.is_empty()
+                    || path
+                        .strip_prefix(prefix.as_str())
+                        .is_some_and(|x| x.is_empty() || x.starts_with('/'))
+            }
+            Matcher::Glob(glob) => glob.is_match(path),
+        }
+    }
+}
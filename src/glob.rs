@@ -0,0 +1,67 @@
+/// [`Glob`] is a simple glob pattern that is matched against a normalized path, such as `"images/logo.svg"`.
+///
+/// The following wildcards are supported, and all other characters match themselves.
+/// - `?` matches any single character except `/`.
+/// - `*` matches any sequence of characters except `/`.
+/// - `**` matches any sequence of characters including `/`. `**/` also matches an empty sequence, so `**/*.html` matches `index.html`.
+///
+/// A leading slash in the pattern is ignored.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Glob {
+    pattern: String,
+}
+
+impl Glob {
+    /// Creates a new `Glob` from a pattern.
+    pub fn new(pattern: impl Into<String>) -> Self {
+        let pattern = pattern.into();
+        Self {
+            pattern: pattern.trim_start_matches('/').to_owned(),
+        }
+    }
+
+    /// Returns the pattern of this glob.
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Returns `true` if `path` matches this glob.
+    pub fn is_match(&self, path: &str) -> bool {
+        matches(
+            self.pattern.as_bytes(),
+            path.trim_start_matches('/').as_bytes(),
+        )
+    }
+}
+
+impl From<&str> for Glob {
+    fn from(pattern: &str) -> Self {
+        Self::new(pattern)
+    }
+}
+
+impl From<String> for Glob {
+    fn from(pattern: String) -> Self {
+        Self::new(pattern)
+    }
+}
+
+fn matches(pattern: &[u8], path: &[u8]) -> bool {
+    match pattern {
+        [] => path.is_empty(),
+        [b'*', b'*', b'/', rest @ ..] => {
+            matches(rest, path)
+                || path
+                    .iter()
+                    .enumerate()
+                    .any(|(index, x)| *x == b'/' && matches(rest, &path[index + 1..]))
+        }
+        [b'*', b'*', rest @ ..] => (0..=path.len()).any(|index| matches(rest, &path[index..])),
+        [b'*', rest @ ..] => {
+            let segment_end = path.iter().position(|x| *x == b'/').unwrap_or(path.len());
+            (0..=segment_end).any(|index| matches(rest, &path[index..]))
+        }
+        [b'?', rest @ ..] => matches!(path, [x, ..] if *x != b'/') && matches(rest, &path[1..]),
+        [x, rest @ ..] => matches!(path, [y, ..] if x == y) && matches(rest, &path[1..]),
+    }
+}
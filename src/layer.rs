@@ -0,0 +1,165 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use http::{Request, Response};
+use http_body_util::Either;
+use tower_layer::Layer;
+use tower_service::Service;
+
+use crate::{AssetSource, ServeBody, ServeEmbed, ServeFuture};
+
+impl<E: AssetSource> ServeEmbed<E> {
+    /// Creates a service that serves the embedded files, and passes the request to `inner` when no file matches.
+    ///
+    /// Please see [`ServeEmbedFallback`] for details.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let api = axum::Router::new().route("/api/users", get(list_users));
+    /// let app = ServeEmbed::<Assets>::new().fallback_service(api);
+    /// ```
+    pub fn fallback_service<S>(self, inner: S) -> ServeEmbedFallback<E, S> {
+        ServeEmbedFallback { serve: self, inner }
+    }
+}
+
+/// [`ServeEmbedLayer`] is a tower [`Layer`] that serves the embedded files in front of the wrapped service.
+///
+/// The wrapped service receives the requests that do not match any file. Please see [`ServeEmbedFallback`] for details.
+///
+/// # Example
+/// ```ignore
+/// let app = tower::ServiceBuilder::new()
+///     .layer(ServeEmbedLayer::new(ServeEmbed::<Assets>::new()))
+///     .service(api_router);
+/// ```
+pub struct ServeEmbedLayer<E: AssetSource> {
+    serve: ServeEmbed<E>,
+}
+
+impl<E: AssetSource> ServeEmbedLayer<E> {
+    /// Creates a new `ServeEmbedLayer` that serves files with `serve`.
+    pub fn new(serve: ServeEmbed<E>) -> Self {
+        Self { serve }
+    }
+}
+
+impl<E: AssetSource> Clone for ServeEmbedLayer<E> {
+    fn clone(&self) -> Self {
+        Self {
+            serve: self.serve.clone(),
+        }
+    }
+}
+
+impl<E: AssetSource> std::fmt::Debug for ServeEmbedLayer<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ServeEmbedLayer")
+            .field("serve", &self.serve)
+            .finish()
+    }
+}
+
+impl<E: AssetSource, S> Layer<S> for ServeEmbedLayer<E> {
+    type Service = ServeEmbedFallback<E, S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        self.serve.clone().fallback_service(inner)
+    }
+}
+
+/// [`ServeEmbedFallback`] is a service that serves the embedded files, and passes the other requests to an inner service.
+///
+/// This service is created by [`ServeEmbed::fallback_service`] or [`ServeEmbedLayer`].
+/// The original request, including its body, is passed to the inner service if no file matches the path,
+/// or if the method is not accepted, including `OPTIONS`. The fallback file and the default 404 page are not used.
+/// A CORS preflight request that matches a [`CorsRule`](crate::CorsRule) is still answered by this service.
+/// Directory redirects are still answered by this service.
+///
+/// The body of a response is [`Either::Left`] for an embedded file and [`Either::Right`] for a response of the inner service.
+pub struct ServeEmbedFallback<E: AssetSource, S> {
+    serve: ServeEmbed<E>,
+    inner: S,
+}
+
+impl<E: AssetSource, S: Clone> Clone for ServeEmbedFallback<E, S> {
+    fn clone(&self) -> Self {
+        Self {
+            serve: self.serve.clone(),
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<E: AssetSource, S: std::fmt::Debug> std::fmt::Debug for ServeEmbedFallback<E, S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ServeEmbedFallback")
+            .field("serve", &self.serve)
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+
+impl<E, S, T, B> Service<Request<T>> for ServeEmbedFallback<E, S>
+where
+    E: AssetSource,
+    S: Service<Request<T>, Response = Response<B>>,
+{
+    type Response = Response<Either<ServeBody, B>>;
+    type Error = S::Error;
+    type Future = FallbackFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<T>) -> Self::Future {
+        let mut serve = ServeFuture::<E, T>::new(&self.serve.bundle, req);
+        serve.fall_through = true;
+        let state = match serve.respond() {
+            Some(response) => State::Ready(Some(serve.finish_response(response))),
+            None => State::Inner(Box::pin(self.inner.call(serve.request))),
+        };
+        FallbackFuture { state }
+    }
+}
+
+/// `FallbackFuture` is a future that represents a response of [`ServeEmbedFallback`].
+/// This future is not intended to be used directly.
+pub struct FallbackFuture<F> {
+    state: State<F>,
+}
+
+enum State<F> {
+    Ready(Option<Response<ServeBody>>),
+    Inner(Pin<Box<F>>),
+}
+
+impl<F> std::fmt::Debug for FallbackFuture<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FallbackFuture").finish_non_exhaustive()
+    }
+}
+
+impl<F, B, Err> Future for FallbackFuture<F>
+where
+    F: Future<Output = Result<Response<B>, Err>>,
+{
+    type Output = Result<Response<Either<ServeBody, B>>, Err>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match &mut self.state {
+            State::Ready(response) => Poll::Ready(Ok(response
+                .take()
+                .expect("FallbackFuture polled after completion")
+                .map(Either::Left))),
+            State::Inner(future) => future
+                .as_mut()
+                .poll(cx)
+                .map_ok(|response| response.map(Either::Right)),
+        }
+    }
+}
@@ -11,7 +11,8 @@
 //! - Serve embedded files over HTTP
 //! - Customizable 404, fallback, and index files
 //! - Response compressed files if the client supports it and the compressed file exists
-//! - Response 304 if the client has the same file (based on ETag)
+//! - Response 304 if the client has the same file (based on ETag or Last-Modified), including fallback files served with 200
+//! - Response `HEAD` with the same headers as `GET`, including `Content-Length`, and an empty body
 //! - Redirect to the directory if the client requests a directory without a trailing slash
 //!
 //! # Example
@@ -39,6 +40,124 @@
 //!
 //! Please see the [examples](https://github.com/informationsea/axum-embed/tree/main/examples) directory for a working example.
 //!
+//! ## Configuration
+//!
+//! Use [`ServeEmbed::builder`] to customize the index file, the fallback file and the fallback behavior.
+//! [`ServeEmbedBuilder::root`] serves only a folder inside the embedded files, for example to serve `admin/` under `/admin`.
+//! [`ServeEmbedBuilder::include`] and [`ServeEmbedBuilder::exclude`] limit the served files with glob patterns, for example to keep templates embedded in the same folder private.
+//! [`ServeEmbedBuilder::spa_mode`] applies the fallback file only to navigation requests, so that a missing script or image gets a genuine 404 instead of the HTML shell of a single page application.
+//! [`ServeEmbedBuilder::method`] serves another method, such as `POST`, in the same way as `GET`. `OPTIONS` gets `204 No Content`, and other methods get `405 Method Not Allowed`, both with an `Allow` header.
+//! [`ServeEmbedBuilder::cors_rule`] allows cross-origin requests for the files that match a glob, such as fonts, with a list of origins, credentials and preflight requests.
+//! [`ServeEmbedBuilder::cross_origin_isolation`] sets `Cross-Origin-Opener-Policy`, `Cross-Origin-Embedder-Policy` and `Cross-Origin-Resource-Policy` by the MIME type of each file, so that WebAssembly apps can use `SharedArrayBuffer`.
+//! `X-Content-Type-Options: nosniff` is added to every response. [`ServeEmbedBuilder::security_headers`] also adds `Content-Security-Policy`, `Referrer-Policy`, `Permissions-Policy` and `X-Frame-Options` to HTML documents, and `security_headers_for` overrides them for a glob.
+//! [`ServeEmbedBuilder::csp_nonce`] writes a fresh random nonce into every `<script>` and `<style>` tag, or into `{{csp_nonce}}` placeholders, and into the `Content-Security-Policy` header.
+//! [`ServeEmbedBuilder::fallback_rule`] sets a different fallback file and fallback behavior for a path prefix or a glob pattern, for example a single page application under `/app/` and a static site under `/docs/`.
+//! [`FallbackBehavior::Status`] serves the fallback file with any status code, such as `503 Service Unavailable` for a maintenance page, and extra headers such as `Retry-After` can be added.
+//! Error responses are negotiated by the `Accept` header: HTML for browsers, `application/problem+json` for API clients and plain text otherwise. [`ServeEmbedBuilder::error_page`] registers an embedded page for a status code and a format.
+//! [`ServeEmbedBuilder::error_templates`] fills `{{path}}`, `{{status}}` and `{{request_id}}` in error pages with escaped values.
+//! [`ServeEmbedBuilder::suggestions`] suggests files with similar paths for a missing path, and `suggestion_redirect` redirects to the file if exactly one suggestion differs only in case or in an extension of the same MIME type.
+//!
+//! ```ignore
+//! let serve_assets = ServeEmbed::<Assets>::builder()
+//!     .index_file("index.html")
+//!     .fallback_file("404.html")
+//!     .fallback_behavior(FallbackBehavior::NotFound)
+//!     .build();
+//! ```
+//!
+//! ## Custom asset sources
+//!
+//! [`ServeEmbed`] serves files from any type that implements [`AssetSource`]. Every [`RustEmbed`] type implements it,
+//! and [`MemorySource`] holds files built at runtime. Implement [`AssetSource`] to serve files from another place,
+//! and use [`ServeEmbed::from_source`] or [`ServeEmbed::builder_from_source`] to create the service.
+//! With the `zip` feature, `ZipSource` serves the entries of a zip archive loaded at runtime.
+//!
+//! [`OverlaySource`] merges several sources in priority order, so that a file in an override bundle shadows the same file in a base bundle.
+//! Index files, compressed files and fallback files are resolved across the merged view.
+//!
+//! ```ignore
+//! let source = OverlaySource::new()
+//!     .embed::<ProductOverrides>()
+//!     .embed::<BaseUi>();
+//! let serve_assets = ServeEmbed::from_source(source);
+//! ```
+//!
+//! ```ignore
+//! let mut source = MemorySource::new();
+//! source.insert("index.html", "<h1>Hello</h1>");
+//! let serve_memory = ServeEmbed::from_source(source);
+//! ```
+//!
+//! ## Override directory
+//!
+//! [`ServeEmbedBuilder::override_dir`] sets a folder on disk whose files win over the embedded files.
+//! Operators can drop a replacement file into the folder without rebuilding the binary. Requests are strictly confined to the folder.
+//!
+//! ```ignore
+//! let serve_assets = ServeEmbed::<Assets>::builder()
+//!     .override_dir("/etc/app/ui-overrides")
+//!     .build();
+//! ```
+//!
+//! ## Development mode
+//!
+//! With the `dev` feature, `ServeEmbedBuilder::dev_folder` serves files from a folder on disk instead of the embedded files,
+//! so edits show up without recompiling. `ServeEmbedBuilder::live_reload` watches the folder and reloads the browser when a file is changed.
+//!
+//! ```ignore
+//! let mut builder = ServeEmbed::<Assets>::builder();
+//! if cfg!(debug_assertions) {
+//!     builder = builder.dev_folder("examples/assets").live_reload(true);
+//! }
+//! let serve_assets = builder.build();
+//! ```
+//!
+//! ## Runtime configuration
+//!
+//! With the `runtime-config` feature, `ServeEmbedBuilder::runtime_config` injects a serializable value into the HTML files as `window.__CONFIG__`,
+//! so that one frontend build works in every environment. The script replaces `<!-- runtime-config -->`, or is inserted before `</head>` or at the start of the document. HTML error pages get the script too.
+//! The JSON is escaped so that a value cannot break out of the script element, and the `ETag` is computed from the injected result.
+//!
+//! ```ignore
+//! let serve_app = ServeEmbed::<Assets>::builder()
+//!     .runtime_config(serde_json::json!({ "apiUrl": api_url, "beta": true }))
+//!     .build();
+//! ```
+//!
+//! ## Fall through to another service
+//!
+//! [`ServeEmbed::fallback_service`] serves the embedded files first and passes the other requests, with the body intact, to another service such as an API router.
+//! [`ServeEmbedLayer`] does the same as a tower layer.
+//!
+//! ```ignore
+//! let api = axum::Router::new().route("/api/users", get(list_users));
+//! let app = axum::Router::new().fallback_service(ServeEmbed::<Assets>::new().fallback_service(api));
+//! ```
+//!
+//! ## Virtual hosts
+//!
+//! [`VirtualHosts`] dispatches a request to a `ServeEmbed` by the `Host` header, so one binary can serve several sites.
+//! Exact host names, wildcard subdomains such as `*.example.com`, and a default host are supported. Each host has its own index and fallback settings.
+//!
+//! ```ignore
+//! let app = VirtualHosts::new()
+//!     .host("docs.example.com", ServeEmbed::<Docs>::new())
+//!     .host("*.example.com", ServeEmbed::<App>::builder().fallback_file("index.html").build())
+//!     .default_host(ServeEmbed::<Landing>::new());
+//! let app = axum::Router::new().fallback_service(app);
+//! ```
+//!
+//! ## Hot-swappable bundles
+//!
+//! [`ServeEmbed::handle`] returns a handle that replaces the files and the settings of a running service, for example after downloading a new zip archive.
+//! Requests that have already started are served from the old bundle. The `X-Bundle-Version` header tells clients which bundle served a response: the version set by `bundle_version`, or a version computed from the files.
+//!
+//! ```ignore
+//! let serve_plugin = ServeEmbed::builder_from_source(plugin_v1).bundle_version("1.0").build();
+//! let handle = serve_plugin.handle();
+//! handle.swap(ServeEmbed::builder_from_source(plugin_v2).bundle_version("1.1").build());
+//! ```
+//!
 //! ## Serve compressed file
 //!
 //! The `axum_embed` library has the capability to serve compressed files, given that the client supports it and the compressed file is available.
@@ -47,18 +166,56 @@
 //! In the absence of client support for any compression methods, `axum_embed` will serve the file in its uncompressed form.
 //! If a file with the extension `.br` (for Brotli), `.gz` (for GZip), or `.zz` (for Deflate) is available, `axum_embed` will serve the file in its compressed form.
 //! An uncompressed file is must be available for the compressed file to be served.
-use std::{borrow::Cow, convert::Infallible, future::Future, pin::Pin, sync::Arc, task::Poll};
+use std::{
+    borrow::Cow,
+    convert::Infallible,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, OnceLock, RwLock},
+    task::Poll,
+};
 
 use bytes::Bytes;
 use chrono::{DateTime, Utc};
 use http::{Request, Response, StatusCode};
-use http_body_util::Full;
 use rust_embed::RustEmbed;
 use tower_service::Service;
 
-#[derive(Clone, RustEmbed)]
-#[folder = "src/assets"]
-struct DefaultFallback;
+mod body;
+mod cors;
+mod dir;
+mod error;
+mod fallback;
+mod glob;
+mod layer;
+#[cfg(feature = "dev")]
+mod live_reload;
+mod nonce;
+mod overlay;
+mod path;
+#[cfg(feature = "runtime-config")]
+mod runtime_config;
+mod security;
+mod source;
+mod suggest;
+mod vhost;
+#[cfg(feature = "zip")]
+mod zip;
+
+pub use body::ServeBody;
+pub use cors::CorsRule;
+pub use dir::DirSource;
+pub use error::ErrorFormat;
+pub use fallback::FallbackRule;
+pub use glob::Glob;
+pub use layer::{FallbackFuture, ServeEmbedFallback, ServeEmbedLayer};
+pub use nonce::CspNonce;
+pub use overlay::OverlaySource;
+pub use security::SecurityHeaders;
+pub use source::{Asset, AssetMetadata, AssetSource, MemorySource};
+pub use vhost::VirtualHosts;
+#[cfg(feature = "zip")]
+pub use zip::ZipSource;
 
 /// [`FallbackBehavior`] is an enumeration representing different behaviors that a server might take when a requested resource is not found.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -69,12 +226,16 @@ pub enum FallbackBehavior {
     Redirect,
     /// The server responds the fallback resource with 200 status code when the resource was not found.
     Ok,
+    /// The server responds the fallback resource with the given status code when the resource was not found,
+    /// such as `410 Gone` for a retired section or `503 Service Unavailable` for a maintenance page.
+    Status(StatusCode),
 }
 
 /// [`ServeEmbed`] is a struct that represents a service for serving embedded files.
 ///
 /// # Parameters
-/// - `E`: A type that implements the [`RustEmbed`] and `Clone` trait. This type represents the embedded files.
+/// - `E`: A type that implements the [`AssetSource`] trait. This type represents the embedded files.
+///   Every [`RustEmbed`] type implements [`AssetSource`].
 ///
 /// # Example
 /// ```ignore
@@ -96,31 +257,143 @@ pub enum FallbackBehavior {
 /// # Ok(())
 /// # }
 /// ```
-#[derive(Debug, Clone)]
-pub struct ServeEmbed<E: RustEmbed + Clone> {
+pub struct ServeEmbed<E: AssetSource> {
     _phantom: std::marker::PhantomData<E>,
-    fallback_file: Arc<Option<String>>,
+    bundle: Arc<RwLock<Bundle>>,
+}
+
+impl<E: AssetSource> Clone for ServeEmbed<E> {
+    fn clone(&self) -> Self {
+        Self {
+            _phantom: std::marker::PhantomData,
+            bundle: self.bundle.clone(),
+        }
+    }
+}
+
+impl<E: AssetSource> std::fmt::Debug for ServeEmbed<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ServeEmbed")
+            .field("config", &self.bundle.read().unwrap().config)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The source and the settings served by a [`ServeEmbed`]. They are replaced together by [`BundleHandle::swap`].
+#[derive(Clone)]
+struct Bundle {
+    source: Arc<dyn AssetSource>,
+    config: Arc<Config>,
+}
+
+/// Settings shared by a [`ServeEmbed`] and every [`ServeFuture`] it creates.
+#[derive(Debug, Clone)]
+struct Config {
+    fallback_file: Option<String>,
     fallback_behavior: FallbackBehavior,
-    index_file: Arc<Option<String>>,
+    fallback_headers: http::HeaderMap,
+    fallback_rules: Vec<FallbackRule>,
+    error_pages: std::collections::HashMap<(StatusCode, ErrorFormat), String>,
+    error_templates: bool,
+    suggestions: bool,
+    suggestion_redirect: bool,
+    suggestion_index: Option<Arc<suggest::PathIndex>>,
+    spa_mode: bool,
+    methods: Vec<http::Method>,
+    cors_rules: Vec<CorsRule>,
+    cross_origin_isolation: bool,
+    security_headers: SecurityHeaders,
+    security_header_rules: Vec<(Glob, SecurityHeaders)>,
+    csp_nonce: Option<CspNonce>,
+    #[cfg(feature = "runtime-config")]
+    runtime_config: Option<runtime_config::RuntimeConfig>,
+    index_file: Option<String>,
+    root: Option<String>,
+    include: Vec<Glob>,
+    exclude: Vec<Glob>,
+    override_dir: Option<std::path::PathBuf>,
+    bundle_version: Option<String>,
+    /// The version computed from the files if no version is set, computed on the first use.
+    content_version: Arc<OnceLock<String>>,
+    #[cfg(feature = "dev")]
+    dev_folder: Option<std::path::PathBuf>,
+    #[cfg(feature = "dev")]
+    live_reload_enabled: bool,
+    #[cfg(feature = "dev")]
+    live_reload: Option<Arc<live_reload::LiveReload>>,
 }
 
-impl<E: RustEmbed + Clone> ServeEmbed<E> {
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            fallback_file: None,
+            fallback_behavior: FallbackBehavior::NotFound,
+            fallback_headers: http::HeaderMap::new(),
+            fallback_rules: Vec::new(),
+            error_pages: std::collections::HashMap::new(),
+            error_templates: false,
+            suggestions: false,
+            suggestion_redirect: false,
+            suggestion_index: None,
+            spa_mode: false,
+            methods: vec![http::Method::GET, http::Method::HEAD],
+            cors_rules: Vec::new(),
+            cross_origin_isolation: false,
+            security_headers: SecurityHeaders::new(),
+            security_header_rules: Vec::new(),
+            csp_nonce: None,
+            #[cfg(feature = "runtime-config")]
+            runtime_config: None,
+            index_file: Some("index.html".to_owned()),
+            root: None,
+            include: Vec::new(),
+            exclude: Vec::new(),
+            override_dir: None,
+            bundle_version: None,
+            content_version: Arc::new(OnceLock::new()),
+            #[cfg(feature = "dev")]
+            dev_folder: None,
+            #[cfg(feature = "dev")]
+            live_reload_enabled: false,
+            #[cfg(feature = "dev")]
+            live_reload: None,
+        }
+    }
+}
+
+impl Config {
+    /// Returns the version set by [`ServeEmbedBuilder::bundle_version`], or the version computed from the files of `source`.
+    fn version(&self, source: &dyn AssetSource) -> &str {
+        match &self.bundle_version {
+            Some(version) => version,
+            None => self
+                .content_version
+                .get_or_init(|| source::content_version(source)),
+        }
+    }
+}
+
+impl<E: RustEmbed + AssetSource> Default for ServeEmbed<E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<E: RustEmbed + AssetSource> ServeEmbed<E> {
     /// Constructs a new `ServeEmbed` instance with default parameters.
     ///
-    /// This function calls `with_parameters` internally with `None` for `fallback_file`, [`FallbackBehavior::NotFound`] for `fallback_behavior`, and `"index.html"` for `index_file`.
+    /// This is equivalent to `ServeEmbed::builder().build()`: no fallback file, [`FallbackBehavior::NotFound`] and `"index.html"` as the index file.
     ///
     /// # Returns
     /// A new `ServeEmbed` instance with default parameters.
     pub fn new() -> Self {
-        Self::with_parameters(
-            None,
-            FallbackBehavior::NotFound,
-            Some("index.html".to_owned()),
-        )
+        Self::builder().build()
     }
 
     /// Constructs a new `ServeEmbed` instance with the provided parameters.
     ///
+    /// This function is kept for compatibility. Please use [`ServeEmbed::builder`] for new code.
+    ///
     /// # Parameters
     /// - `fallback_file`: The path of the file to serve when a requested file is not found. If `None`, a default 404 response is served.
     /// - `fallback_behavior`: The behavior of the server when a requested file is not found. Please see [`FallbackBehavior`] for more information.
@@ -133,17 +406,490 @@ impl<E: RustEmbed + Clone> ServeEmbed<E> {
         fallback_behavior: FallbackBehavior,
         index_file: Option<String>,
     ) -> Self {
+        let mut builder = Self::builder().fallback_behavior(fallback_behavior);
+        builder.config.fallback_file = fallback_file;
+        builder.config.index_file = index_file;
+        builder.build()
+    }
+
+    /// Creates a [`ServeEmbedBuilder`] initialized with the default parameters.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let serve_assets = ServeEmbed::<Assets>::builder()
+    ///     .fallback_file("404.html")
+    ///     .fallback_behavior(FallbackBehavior::Ok)
+    ///     .build();
+    /// ```
+    pub fn builder() -> ServeEmbedBuilder<E> {
+        ServeEmbedBuilder {
+            _phantom: std::marker::PhantomData,
+            source: Arc::new(source::Embedded::<E>::new()),
+            config: Config::default(),
+        }
+    }
+}
+
+impl<E: AssetSource> ServeEmbed<E> {
+    /// Constructs a new `ServeEmbed` instance that serves files from `source` with default parameters.
+    ///
+    /// Use this function for an [`AssetSource`] that is not a [`RustEmbed`] type, such as [`MemorySource`].
+    pub fn from_source(source: E) -> Self {
+        Self::builder_from_source(source).build()
+    }
+
+    /// Creates a [`ServeEmbedBuilder`] that serves files from `source`, initialized with the default parameters.
+    pub fn builder_from_source(source: E) -> ServeEmbedBuilder<E> {
+        ServeEmbedBuilder {
+            _phantom: std::marker::PhantomData,
+            source: Arc::new(source),
+            config: Config::default(),
+        }
+    }
+}
+
+/// [`ServeEmbedBuilder`] configures and creates a [`ServeEmbed`].
+///
+/// A builder is created by [`ServeEmbed::builder`] or [`ServeEmbed::builder_from_source`]. Each method consumes and returns the builder so that calls can be chained,
+/// and [`ServeEmbedBuilder::build`] creates the service.
+pub struct ServeEmbedBuilder<E: AssetSource> {
+    _phantom: std::marker::PhantomData<E>,
+    source: Arc<dyn AssetSource>,
+    config: Config,
+}
+
+impl<E: AssetSource> Clone for ServeEmbedBuilder<E> {
+    fn clone(&self) -> Self {
         Self {
             _phantom: std::marker::PhantomData,
-            fallback_file: Arc::new(fallback_file),
-            fallback_behavior,
-            index_file: Arc::new(index_file),
+            source: self.source.clone(),
+            config: self.config.clone(),
+        }
+    }
+}
+
+impl<E: AssetSource> std::fmt::Debug for ServeEmbedBuilder<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ServeEmbedBuilder")
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<E: AssetSource> ServeEmbedBuilder<E> {
+    /// Sets the name of the file to serve when a directory is accessed. The default is `"index.html"`.
+    pub fn index_file(mut self, index_file: impl Into<String>) -> Self {
+        self.config.index_file = Some(index_file.into());
+        self
+    }
+
+    /// Disables the index file. A 404 response is served for directories.
+    pub fn no_index_file(mut self) -> Self {
+        self.config.index_file = None;
+        self
+    }
+
+    /// Sets the path of the file to serve when a requested file is not found.
+    ///
+    /// If no fallback file is set, a default 404 response is served.
+    pub fn fallback_file(mut self, fallback_file: impl Into<String>) -> Self {
+        self.config.fallback_file = Some(fallback_file.into());
+        self
+    }
+
+    /// Sets the behavior of the server when a requested file is not found. The default is [`FallbackBehavior::NotFound`].
+    pub fn fallback_behavior(mut self, fallback_behavior: FallbackBehavior) -> Self {
+        self.config.fallback_behavior = fallback_behavior;
+        self
+    }
+
+    /// Adds a header to the responses that serve the fallback file, such as `Retry-After` for a maintenance page.
+    ///
+    /// The header is not added to the responses of a [`FallbackRule`]. Please use [`FallbackRule::header`] for them.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let serve_maintenance = ServeEmbed::<Assets>::builder()
+    ///     .fallback_file("maintenance.html")
+    ///     .fallback_behavior(FallbackBehavior::Status(StatusCode::SERVICE_UNAVAILABLE))
+    ///     .fallback_header(header::RETRY_AFTER, HeaderValue::from_static("120"))
+    ///     .build();
+    /// ```
+    pub fn fallback_header(mut self, name: http::HeaderName, value: http::HeaderValue) -> Self {
+        self.config.fallback_headers.append(name, value);
+        self
+    }
+
+    /// Adds a [`FallbackRule`] that sets the fallback file and the fallback behavior for a part of the path space.
+    ///
+    /// Rules are evaluated in the order they were added, and the first matching rule wins over [`ServeEmbedBuilder::fallback_file`] and [`ServeEmbedBuilder::fallback_behavior`].
+    pub fn fallback_rule(mut self, rule: FallbackRule) -> Self {
+        self.config.fallback_rules.push(rule);
+        self
+    }
+
+    /// Registers a file of the source as the body of the error responses with `status` in `format`.
+    ///
+    /// An error response is served when a requested file is not found and there is no fallback file,
+    /// or when the method is not allowed. The format is chosen by the `Accept` header. Please see [`ErrorFormat`] for details.
+    /// If no page is registered, or the file is not found, a built-in body is served.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let serve_assets = ServeEmbed::<Assets>::builder()
+    ///     .error_page(StatusCode::NOT_FOUND, ErrorFormat::Html, "errors/404.html")
+    ///     .error_page(StatusCode::NOT_FOUND, ErrorFormat::ProblemJson, "errors/404.json")
+    ///     .build();
+    /// ```
+    pub fn error_page(
+        mut self,
+        status: StatusCode,
+        format: ErrorFormat,
+        path: impl Into<String>,
+    ) -> Self {
+        self.config
+            .error_pages
+            .insert((status, format), path.into());
+        self
+    }
+
+    /// Fills the placeholders in the body of error responses. The default is `false`.
+    ///
+    /// The placeholders are replaced in a fallback file served with an error status code, and in a page registered by [`ServeEmbedBuilder::error_page`].
+    /// - `{{path}}`: the decoded path of the request
+    /// - `{{status}}`: the status code, such as `404`
+    /// - `{{request_id}}`: the `X-Request-Id` header of the request, or an empty string
+    /// - `{{suggestion}}`, `{{suggestions}}`: the closest path and a comma-separated list of close paths, as URLs relative to the request,
+    ///   if [`ServeEmbedBuilder::suggestions`] is enabled
+    ///
+    /// Values are escaped for HTML, XML and JSON bodies. A response with a filled placeholder has `Cache-Control: no-store` and no `ETag`,
+    /// because the body differs for each request.
+    ///
+    /// # Example
+    /// ```ignore
+    /// // 404.html: <p>{{path}} was not found. Request ID: {{request_id}}</p>
+    /// let serve_assets = ServeEmbed::<Assets>::builder()
+    ///     .fallback_file("404.html")
+    ///     .error_templates(true)
+    ///     .build();
+    /// ```
+    pub fn error_templates(mut self, enabled: bool) -> Self {
+        self.config.error_templates = enabled;
+        self
+    }
+
+    /// Suggests files whose path is close to a path that was not found. The default is `false`.
+    ///
+    /// An index of the paths is built once by [`ServeEmbedBuilder::build`]. Paths are compared case-insensitively by edit distance,
+    /// so that a typo, wrong case or a wrong extension is found. Up to three suggestions are added to error responses
+    /// as a `Link` header with `rel="alternate"`, and to error pages as placeholders. Please see [`ServeEmbedBuilder::error_templates`].
+    ///
+    /// # Example
+    /// ```ignore
+    /// // 404.html: <p>Did you mean <a href="{{suggestion}}">{{suggestion}}</a>?</p>
+    /// let serve_assets = ServeEmbed::<Assets>::builder()
+    ///     .fallback_file("404.html")
+    ///     .error_templates(true)
+    ///     .suggestions(true)
+    ///     .build();
+    /// ```
+    pub fn suggestions(mut self, enabled: bool) -> Self {
+        self.config.suggestions = enabled;
+        self
+    }
+
+    /// Redirects to a suggested file if exactly one suggestion has high confidence. The default is `false`.
+    ///
+    /// A suggestion has high confidence if the path differs only in case, or in a file extension of the same MIME type,
+    /// such as `.htm` for `.html`.
+    /// The redirect is temporary, with `307 Temporary Redirect`. Enabling this also enables [`ServeEmbedBuilder::suggestions`].
+    pub fn suggestion_redirect(mut self, enabled: bool) -> Self {
+        self.config.suggestion_redirect = enabled;
+        if enabled {
+            self.config.suggestions = true;
+        }
+        self
+    }
+
+    /// Applies the fallback file only to navigation requests, for a single page application. The default is `false`.
+    ///
+    /// A request is a navigation request if the last segment of the path has no file extension, the `Accept` header contains `text/html`,
+    /// or the `Sec-Fetch-Mode` header is `navigate`. Other requests for a missing file get the default 404 response,
+    /// so that a missing script or image is not answered with the HTML shell.
+    /// Responses for a missing file have `Vary: Accept, Sec-Fetch-Mode`, so that caches do not mix up the two.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let serve_app = ServeEmbed::<Assets>::builder()
+    ///     .fallback_file("index.html")
+    ///     .fallback_behavior(FallbackBehavior::Ok)
+    ///     .spa_mode(true)
+    ///     .build();
+    /// ```
+    pub fn spa_mode(mut self, enabled: bool) -> Self {
+        self.config.spa_mode = enabled;
+        self
+    }
+
+    /// Serves the requests with `method` in the same way as `GET`. `GET` and `HEAD` are always served.
+    ///
+    /// Requests with other methods get `405 Method Not Allowed`, except for `OPTIONS`, which gets `204 No Content`.
+    /// Both responses have an `Allow` header with the accepted methods.
+    ///
+    /// # Example
+    /// ```ignore
+    /// // answer a form post to the single page application with the HTML shell
+    /// let serve_app = ServeEmbed::<Assets>::builder()
+    ///     .fallback_file("index.html")
+    ///     .fallback_behavior(FallbackBehavior::Ok)
+    ///     .method(Method::POST)
+    ///     .build();
+    /// ```
+    pub fn method(mut self, method: http::Method) -> Self {
+        if !self.config.methods.contains(&method) {
+            self.config.methods.push(method);
+        }
+        self
+    }
+
+    /// Adds a [`CorsRule`] that allows cross-origin requests for a part of the path space.
+    ///
+    /// Rules are evaluated in the order they were added, and the first matching rule is used.
+    pub fn cors_rule(mut self, rule: CorsRule) -> Self {
+        self.config.cors_rules.push(rule);
+        self
+    }
+
+    /// Sets the headers that make the served pages cross-origin isolated, so that they can use `SharedArrayBuffer`. The default is `false`.
+    ///
+    /// The headers are chosen by the MIME type of each response:
+    /// - HTML documents get `Cross-Origin-Opener-Policy: same-origin` and `Cross-Origin-Embedder-Policy: require-corp`.
+    /// - JavaScript files get `Cross-Origin-Embedder-Policy: require-corp`, so that they can be loaded as workers.
+    /// - Every response gets `Cross-Origin-Resource-Policy: same-origin`. Please use [`CorsRule`] to share files with other origins.
+    pub fn cross_origin_isolation(mut self, enabled: bool) -> Self {
+        self.config.cross_origin_isolation = enabled;
+        self
+    }
+
+    /// Adds [`SecurityHeaders`] to every response. The default is [`SecurityHeaders::new`], which adds only `X-Content-Type-Options: nosniff`.
+    pub fn security_headers(mut self, headers: SecurityHeaders) -> Self {
+        self.config.security_headers = headers;
+        self
+    }
+
+    /// Uses `headers` instead of [`ServeEmbedBuilder::security_headers`] for the paths that match `pattern`. Please see [`Glob`] for the syntax.
+    ///
+    /// Overrides are evaluated in the order they were added, and the first matching override wins.
+    pub fn security_headers_for(
+        mut self,
+        pattern: impl Into<Glob>,
+        headers: SecurityHeaders,
+    ) -> Self {
+        self.config
+            .security_header_rules
+            .push((pattern.into(), headers));
+        self
+    }
+
+    /// Writes a random nonce, generated for each request, into the HTML files and the `Content-Security-Policy` header.
+    ///
+    /// Please see [`CspNonce`] for details.
+    pub fn csp_nonce(mut self, mode: CspNonce) -> Self {
+        self.config.csp_nonce = Some(mode);
+        self
+    }
+
+    /// Injects `value` into the HTML files as `window.__CONFIG__`, such as the URL of an API and feature flags.
+    ///
+    /// The script replaces the marker comment `<!-- runtime-config -->`, or is inserted before `</head>` if there is no marker.
+    /// If there is no `</head>` either, the script is inserted at the start of the document, after `<!DOCTYPE html>`.
+    /// The script is also inserted into HTML error responses.
+    /// The value is serialized to JSON, and `<`, `>` and `&` are escaped, so that a string in the value cannot close the script element.
+    /// The `ETag` of an HTML file is computed from the injected result.
+    /// If [`ServeEmbedBuilder::csp_nonce`] is set, the script has the nonce of the request.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let serve_app = ServeEmbed::<Assets>::builder()
+    ///     .runtime_config(serde_json::json!({ "apiUrl": std::env::var("API_URL")? }))
+    ///     .build();
+    /// ```
+    #[cfg(feature = "runtime-config")]
+    pub fn runtime_config<V>(mut self, value: V) -> Self
+    where
+        V: serde::Serialize + Send + Sync + 'static,
+    {
+        self.config.runtime_config = Some(runtime_config::RuntimeConfig::value(value));
+        self
+    }
+
+    /// Injects the value returned by `f`, which is called for each request, into the HTML files as `window.__CONFIG__`.
+    ///
+    /// Please see [`ServeEmbedBuilder::runtime_config`] for details.
+    #[cfg(feature = "runtime-config")]
+    pub fn runtime_config_with<F, V>(mut self, f: F) -> Self
+    where
+        F: Fn() -> V + Send + Sync + 'static,
+        V: serde::Serialize,
+    {
+        self.config.runtime_config = Some(runtime_config::RuntimeConfig::from_fn(f));
+        self
+    }
+
+    /// Serves only the files under `root`, a folder inside the source, as if it were the root of the source.
+    ///
+    /// Every request is resolved relative to `root`, and files outside of it are unreachable.
+    /// The index file and the fallback file are also relative to `root`.
+    /// Redirects use a relative `Location`, so that they stay under the mount point when the service is nested.
+    ///
+    /// # Example
+    /// ```ignore
+    /// // serve `dist/admin/` of the embedded files at `/admin`
+    /// let serve_admin = ServeEmbed::<Dist>::builder()
+    ///     .root("admin")
+    ///     .fallback_file("index.html")
+    ///     .fallback_behavior(FallbackBehavior::Ok)
+    ///     .build();
+    /// let app = axum::Router::new().nest_service("/admin", serve_admin);
+    /// ```
+    pub fn root(mut self, root: impl Into<String>) -> Self {
+        let root = root.into();
+        let root = root.trim_matches('/');
+        self.config.root = if root.is_empty() {
+            None
+        } else {
+            Some(root.to_owned())
+        };
+        self
+    }
+
+    /// Adds a glob pattern of the files that are allowed to be served. Please see [`Glob`] for the syntax.
+    ///
+    /// If at least one include pattern is added, only the files whose path matches one of them are served.
+    /// Patterns are matched against the normalized path relative to the root, such as `"images/logo.svg"`.
+    /// A file that is not allowed is treated as nonexistent for lookup, index file probing and compressed file selection.
+    pub fn include(mut self, pattern: impl Into<Glob>) -> Self {
+        self.config.include.push(pattern.into());
+        self
+    }
+
+    /// Adds a glob pattern of the files that must never be served. Please see [`Glob`] for the syntax.
+    ///
+    /// Exclude patterns take precedence over include patterns.
+    /// A file that is denied is treated as nonexistent for lookup, index file probing and compressed file selection.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let serve_public = ServeEmbed::<Assets>::builder()
+    ///     .exclude("templates/**")
+    ///     .exclude("**/*.tera")
+    ///     .build();
+    /// ```
+    pub fn exclude(mut self, pattern: impl Into<Glob>) -> Self {
+        self.config.exclude.push(pattern.into());
+        self
+    }
+
+    /// Sets a folder on disk whose files win over the files of the source.
+    ///
+    /// Operators can drop a replacement file, such as `logo.svg` or `config.json`, into the folder without rebuilding the binary.
+    /// A file in the folder is served with the same `ETag`, MIME type and conditional request handling as an embedded file.
+    /// Requests are strictly confined to the folder. Please see [`DirSource`] for details.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let serve_assets = ServeEmbed::<Assets>::builder()
+    ///     .override_dir("/etc/app/ui-overrides")
+    ///     .build();
+    /// ```
+    pub fn override_dir(mut self, folder: impl Into<std::path::PathBuf>) -> Self {
+        self.config.override_dir = Some(folder.into());
+        self
+    }
+
+    /// Sets the version of the bundle. The version is sent in the `X-Bundle-Version` header of every response.
+    /// Without a version, a version is computed from the paths and the hashes of the files when it is first needed.
+    ///
+    /// Clients can compare the header with the version they have loaded to detect a swap by [`BundleHandle::swap`].
+    pub fn bundle_version(mut self, version: impl Into<String>) -> Self {
+        self.config.bundle_version = Some(version.into());
+        self
+    }
+
+    /// Serves files from `folder` on disk instead of the embedded files. This method is available with the `dev` feature.
+    ///
+    /// Files are read for every request, so edits show up without recompiling, whether or not the `debug-embed` feature of `rust_embed` is enabled.
+    /// Please see [`DirSource`] for details.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let mut builder = ServeEmbed::<Assets>::builder();
+    /// if cfg!(debug_assertions) {
+    ///     builder = builder.dev_folder("examples/assets").live_reload(true);
+    /// }
+    /// let serve_assets = builder.build();
+    /// ```
+    #[cfg(feature = "dev")]
+    pub fn dev_folder(mut self, folder: impl Into<std::path::PathBuf>) -> Self {
+        let folder = folder.into();
+        self.source = Arc::new(DirSource::new(folder.clone()));
+        self.config.dev_folder = Some(folder);
+        self
+    }
+
+    /// Enables live reload. This method is available with the `dev` feature and takes effect only with [`ServeEmbedBuilder::dev_folder`].
+    ///
+    /// The folder is watched for changes, and a small script is inserted into HTML responses.
+    /// The script listens on a Server-Sent Events endpoint at `__axum_embed/live-reload` under the root of the service,
+    /// and reloads the page when a file is changed. Compressed variants of HTML files are not served while live reload is enabled.
+    #[cfg(feature = "dev")]
+    pub fn live_reload(mut self, enabled: bool) -> Self {
+        self.config.live_reload_enabled = enabled;
+        self
+    }
+
+    /// Creates a [`ServeEmbed`] with the configured parameters.
+    pub fn build(mut self) -> ServeEmbed<E> {
+        if let Some(root) = &self.config.root {
+            self.source = Arc::new(source::Subdir::new(self.source, root));
+        }
+        if let Some(folder) = &self.config.override_dir {
+            self.source = Arc::new(
+                OverlaySource::new()
+                    .layer(DirSource::new(folder))
+                    .shared_layer(self.source),
+            );
+        }
+        if !self.config.include.is_empty() || !self.config.exclude.is_empty() {
+            self.source = Arc::new(source::Filtered::new(
+                self.source,
+                self.config.include.clone(),
+                self.config.exclude.clone(),
+            ));
+        }
+
+        if self.config.suggestions {
+            self.config.suggestion_index =
+                Some(Arc::new(suggest::PathIndex::new(self.source.as_ref())));
+        }
+
+        #[cfg(feature = "dev")]
+        if let (true, Some(folder)) = (self.config.live_reload_enabled, &self.config.dev_folder) {
+            self.config.live_reload = Some(live_reload::LiveReload::watch(folder.clone()));
+        }
+
+        ServeEmbed {
+            _phantom: std::marker::PhantomData,
+            bundle: Arc::new(RwLock::new(Bundle {
+                source: self.source,
+                config: Arc::new(self.config),
+            })),
         }
     }
 }
 
-impl<E: RustEmbed + Clone, T: Send + 'static> Service<http::request::Request<T>> for ServeEmbed<E> {
-    type Response = http::Response<Full<Bytes>>;
+impl<E: AssetSource, T: Send + 'static> Service<http::request::Request<T>> for ServeEmbed<E> {
+    type Response = http::Response<ServeBody>;
     type Error = Infallible;
     type Future = ServeFuture<E, T>;
 
@@ -155,26 +901,89 @@ impl<E: RustEmbed + Clone, T: Send + 'static> Service<http::request::Request<T>>
     }
 
     fn call(&mut self, req: http::request::Request<T>) -> Self::Future {
-        ServeFuture {
-            _phantom: std::marker::PhantomData,
-            fallback_behavior: self.fallback_behavior,
-            fallback_file: self.fallback_file.clone(),
-            index_file: self.index_file.clone(),
-            request: req,
+        ServeFuture::new(&self.bundle, req)
+    }
+}
+
+impl<E: AssetSource> ServeEmbed<E> {
+    /// Returns a [`BundleHandle`] that replaces the files and the settings this service serves.
+    pub fn handle(&self) -> BundleHandle {
+        BundleHandle {
+            bundle: self.bundle.clone(),
         }
     }
 }
 
+/// [`BundleHandle`] replaces the files and the settings of a running [`ServeEmbed`] without restarting it.
+///
+/// A handle is created by [`ServeEmbed::handle`], and it is shared with every clone of the service.
+/// [`BundleHandle::swap`] replaces the source and the settings at once. Requests that have already started are served from the old bundle,
+/// and new requests are served from the new bundle.
+///
+/// Clients can detect the swap by the `X-Bundle-Version` header. It is the version set by [`ServeEmbedBuilder::bundle_version`],
+/// or a version computed from the files of the bundle.
+///
+/// # Example
+/// ```ignore
+/// let serve_plugin = ServeEmbed::builder_from_source(ZipSource::open("plugin-1.0.zip")?)
+///     .bundle_version("1.0")
+///     .build();
+/// let handle = serve_plugin.handle();
+///
+/// // after downloading a new version of the plugin
+/// handle.swap(
+///     ServeEmbed::builder_from_source(ZipSource::open("plugin-1.1.zip")?)
+///         .bundle_version("1.1")
+///         .build(),
+/// );
+/// ```
+#[derive(Clone)]
+pub struct BundleHandle {
+    bundle: Arc<RwLock<Bundle>>,
+}
+
+impl std::fmt::Debug for BundleHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BundleHandle")
+            .field("bundle_version", &self.bundle_version())
+            .finish_non_exhaustive()
+    }
+}
+
+impl BundleHandle {
+    /// Replaces the source and the settings with the ones of `service`.
+    ///
+    /// `service` can serve a different type of [`AssetSource`]. It is not affected by the swap, and later swaps of its own handle are not reflected.
+    pub fn swap<S: AssetSource>(&self, service: ServeEmbed<S>) {
+        let bundle = service.bundle.read().unwrap().clone();
+        *self.bundle.write().unwrap() = bundle;
+    }
+
+    /// Returns the version of the bundle that is currently served, which is sent in the `X-Bundle-Version` header.
+    ///
+    /// It is the version set by [`ServeEmbedBuilder::bundle_version`], or a version computed from the files of the bundle.
+    pub fn bundle_version(&self) -> String {
+        let bundle = self.bundle.read().unwrap();
+        bundle.config.version(bundle.source.as_ref()).to_owned()
+    }
+}
+
+/// [`CompressionMethod`] is an enumeration of the content encodings that [`ServeEmbed`] can respond with.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-enum CompressionMethod {
+pub enum CompressionMethod {
+    /// The file is not compressed.
     Identity,
+    /// The file is compressed with Brotli (`Content-Encoding: br`).
     Brotli,
+    /// The file is compressed with GZip (`Content-Encoding: gzip`).
     Gzip,
+    /// The file is compressed with zlib (`Content-Encoding: deflate`).
     Zlib,
 }
 
 impl CompressionMethod {
-    fn extension(self) -> &'static str {
+    /// Returns the file extension of a compressed sidecar file, such as `".br"`. An empty string is returned for [`CompressionMethod::Identity`].
+    pub fn extension(self) -> &'static str {
         match self {
             Self::Identity => "",
             Self::Brotli => ".br",
@@ -209,16 +1018,9 @@ fn from_acceptable_encoding(acceptable_encoding: Option<&str>) -> Vec<Compressio
     compression_methods
 }
 
-fn cow_to_bytes(cow: Cow<'static, [u8]>) -> Bytes {
-    match cow {
-        Cow::Borrowed(x) => Bytes::from(x),
-        Cow::Owned(x) => Bytes::from(x),
-    }
-}
-
 struct GetFileResult<'a> {
     path: Cow<'a, str>,
-    file: Option<rust_embed::EmbeddedFile>,
+    file: Option<Asset>,
     should_redirect: Option<String>,
     compression_method: CompressionMethod,
     is_fallback: bool,
@@ -227,16 +1029,42 @@ struct GetFileResult<'a> {
 /// `ServeFuture` is a future that represents a service for serving embedded files.
 /// This future is created by `ServeEmbed`.
 /// This future is not intended to be used directly.
-#[derive(Debug, Clone)]
-pub struct ServeFuture<E: RustEmbed, T> {
+#[derive(Clone)]
+pub struct ServeFuture<E, T> {
     _phantom: std::marker::PhantomData<E>,
-    fallback_behavior: FallbackBehavior,
-    fallback_file: Arc<Option<String>>,
-    index_file: Arc<Option<String>>,
+    source: Arc<dyn AssetSource>,
+    config: Arc<Config>,
     request: Request<T>,
+    fall_through: bool,
+    suggestions: std::cell::OnceCell<Vec<suggest::Suggestion>>,
+    nonce: std::cell::OnceCell<String>,
 }
 
-impl<E: RustEmbed, T> ServeFuture<E, T> {
+impl<E, T: std::fmt::Debug> std::fmt::Debug for ServeFuture<E, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ServeFuture")
+            .field("config", &self.config)
+            .field("request", &self.request)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<E, T> ServeFuture<E, T> {
+    /// Creates a future that serves `request` from the current bundle.
+    /// The bundle is captured here, so that a swap does not affect the request.
+    fn new(bundle: &RwLock<Bundle>, request: Request<T>) -> Self {
+        let bundle = bundle.read().unwrap();
+        Self {
+            _phantom: std::marker::PhantomData,
+            source: bundle.source.clone(),
+            config: bundle.config.clone(),
+            request,
+            fall_through: false,
+            suggestions: std::cell::OnceCell::new(),
+            nonce: std::cell::OnceCell::new(),
+        }
+    }
+
     /// Attempts to get a file from the embedded files based on the provided path and acceptable encodings.
     ///
     /// # Parameters
@@ -252,25 +1080,28 @@ impl<E: RustEmbed, T> ServeFuture<E, T> {
     ) -> GetFileResult<'a> {
         let mut path_candidate = Cow::Borrowed(path.trim_start_matches('/'));
 
-        if path_candidate == "" {
-            if let Some(index_file) = self.index_file.as_ref() {
+        if path_candidate.is_empty() {
+            if let Some(index_file) = self.config.index_file.as_ref() {
                 path_candidate = Cow::Owned(index_file.to_string());
             }
         } else if path_candidate.ends_with('/') {
-            if let Some(index_file) = self.index_file.as_ref().as_ref() {
+            if let Some(index_file) = self.config.index_file.as_ref() {
                 let new_path_candidate = format!("{}{}", path_candidate, index_file);
-                if E::get(&new_path_candidate).is_some() {
+                if self.source.get(&new_path_candidate).is_some() {
                     path_candidate = Cow::Owned(new_path_candidate);
                 }
             }
         } else {
-            if let Some(index_file) = self.index_file.as_ref().as_ref() {
+            if let Some(index_file) = self.config.index_file.as_ref() {
                 let new_path_candidate = format!("{}/{}", path_candidate, index_file);
-                if E::get(&new_path_candidate).is_some() {
+                if self.source.get(&new_path_candidate).is_some() {
                     return GetFileResult {
                         path: Cow::Owned(new_path_candidate),
                         file: None,
-                        should_redirect: Some(format!("/{}/", path_candidate)),
+                        should_redirect: Some(path::relative(
+                            self.request.uri().path(),
+                            &format!("{}/", path_candidate),
+                        )),
                         compression_method: CompressionMethod::Identity,
                         is_fallback: false,
                     };
@@ -278,12 +1109,12 @@ impl<E: RustEmbed, T> ServeFuture<E, T> {
             }
         }
 
-        let mut file = E::get(&path_candidate);
+        let mut file = self.source.get(&path_candidate);
         let mut compressed_method = CompressionMethod::Identity;
 
         if file.is_some() {
             for one_method in acceptable_encoding {
-                if let Some(x) = E::get(&format!("{}{}", path_candidate, one_method.extension())) {
+                if let Some(x) = self.source.get_compressed(&path_candidate, *one_method) {
                     file = Some(x);
                     compressed_method = *one_method;
                     break;
@@ -300,21 +1131,38 @@ impl<E: RustEmbed, T> ServeFuture<E, T> {
         }
     }
 
+    /// Attempts to get a file with [`ServeFuture::get_file`], and falls back to the fallback file or the default 404 page.
+    ///
+    /// `path` is the normalized path of the request, or `None` if the path is invalid. An invalid path is treated as a missing file.
     fn get_file_with_fallback<'a, 'b: 'a>(
         &'b self,
-        path: &'a str,
+        path: Option<&'a str>,
         acceptable_encoding: &[CompressionMethod],
     ) -> GetFileResult<'a> {
-        let first_try = self.get_file(path, acceptable_encoding);
-        if first_try.file.is_some() || first_try.should_redirect.is_some() {
-            return first_try;
+        if let Some(path) = path {
+            let first_try = self.get_file(path, acceptable_encoding);
+            if first_try.file.is_some() || first_try.should_redirect.is_some() {
+                return first_try;
+            }
         }
-        if let Some(fallback_file) = self.fallback_file.as_ref().as_ref() {
-            if fallback_file != path && self.fallback_behavior == FallbackBehavior::Redirect {
+        if self.fall_through {
+            return GetFileResult {
+                path: Cow::Borrowed(path.unwrap_or_default()),
+                file: None,
+                should_redirect: None,
+                compression_method: CompressionMethod::Identity,
+                is_fallback: true,
+            };
+        }
+        let (fallback_file, fallback_behavior, _) = self.fallback(path);
+        if let Some(fallback_file) =
+            fallback_file.filter(|_| !self.config.spa_mode || self.is_navigation())
+        {
+            if path != Some(fallback_file) && fallback_behavior == FallbackBehavior::Redirect {
                 return GetFileResult {
-                    path: Cow::Borrowed(path),
+                    path: Cow::Borrowed(path.unwrap_or_default()),
                     file: None,
-                    should_redirect: Some(format!("/{}", fallback_file)),
+                    should_redirect: Some(path::relative(self.request.uri().path(), fallback_file)),
                     compression_method: CompressionMethod::Identity,
                     is_fallback: true,
                 };
@@ -326,40 +1174,164 @@ impl<E: RustEmbed, T> ServeFuture<E, T> {
             }
         }
         GetFileResult {
-            path: Cow::Borrowed("404.html"),
-            file: DefaultFallback::get("404.html"),
+            path: Cow::Borrowed(path.unwrap_or_default()),
+            file: None,
             should_redirect: None,
             compression_method: CompressionMethod::Identity,
             is_fallback: true,
         }
     }
-}
 
-impl<E: RustEmbed, T> Future for ServeFuture<E, T> {
-    type Output = Result<Response<Full<Bytes>>, Infallible>;
+    /// Returns the fallback file, the fallback behavior and the extra headers for `path`,
+    /// from the first matching [`FallbackRule`] or from the global settings.
+    fn fallback(&self, path: Option<&str>) -> (Option<&str>, FallbackBehavior, &http::HeaderMap) {
+        path.and_then(|path| self.config.fallback_rules.iter().find(|x| x.is_match(path)))
+            .map(|x| (Some(x.file.as_str()), x.behavior, &x.headers))
+            .unwrap_or((
+                self.config.fallback_file.as_deref(),
+                self.config.fallback_behavior,
+                &self.config.fallback_headers,
+            ))
+    }
 
-    fn poll(self: Pin<&mut Self>, _cx: &mut std::task::Context<'_>) -> Poll<Self::Output> {
-        // Accept only GET and HEAD method
-        if self.request.method() != http::Method::GET && self.request.method() != http::Method::HEAD
+    /// Returns `true` if the request looks like a navigation of a browser.
+    ///
+    /// The last segment of the path has no file extension, the `Accept` header contains `text/html`, or the `Sec-Fetch-Mode` header is `navigate`.
+    fn is_navigation(&self) -> bool {
+        let headers = self.request.headers();
+        let file_name = self.request.uri().path().rsplit('/').next().unwrap_or("");
+        !file_name.contains('.')
+            || headers
+                .get_all(http::header::ACCEPT)
+                .iter()
+                .filter_map(|x| x.to_str().ok())
+                .flat_map(|x| x.split(','))
+                .any(|x| x.split(';').next().unwrap().trim() == "text/html")
+            || headers
+                .get("sec-fetch-mode")
+                .is_some_and(|x| x.as_bytes() == b"navigate")
+    }
+
+    /// Inserts the runtime configuration script into an HTML file if a configuration is set.
+    ///
+    /// The script is inserted into the uncompressed file, and the `ETag` is computed from the result.
+    #[cfg(feature = "runtime-config")]
+    fn inject_runtime_config(
+        &self,
+        path: &str,
+        file: Asset,
+        compression_method: CompressionMethod,
+    ) -> (Asset, CompressionMethod) {
+        let Some(runtime_config) = self.config.runtime_config.as_ref().filter(|_| {
+            is_document(
+                mime_guess::from_path(path)
+                    .first_or_octet_stream()
+                    .essence_str(),
+            )
+        }) else {
+            return (file, compression_method);
+        };
+        let Some(data) = self.uncompressed_data(path, &file, compression_method) else {
+            return (file, compression_method);
+        };
+        match runtime_config.inject(&data, self.config.csp_nonce) {
+            Some(injected) => {
+                let mut injected = Asset::new(injected);
+                injected.metadata.last_modified = file.metadata.last_modified;
+                (injected, CompressionMethod::Identity)
+            }
+            None => (file, compression_method),
+        }
+    }
+
+    /// Inserts the live reload script into an HTML file if live reload is enabled.
+    ///
+    /// The script is inserted into the uncompressed file, and the `ETag` is computed from the result.
+    #[cfg(feature = "dev")]
+    fn inject_live_reload(
+        &self,
+        path: &str,
+        file: Asset,
+        compression_method: CompressionMethod,
+    ) -> (Asset, CompressionMethod) {
+        if self.config.live_reload.is_none()
+            || mime_guess::from_path(path).first_or_octet_stream() != mime_guess::mime::TEXT_HTML
         {
-            return Poll::Ready(Ok(Response::builder()
-                .status(StatusCode::METHOD_NOT_ALLOWED)
-                .header(http::header::CONTENT_TYPE, "text/plain")
-                .body(Full::new(Bytes::from("Method not allowed")))
-                .unwrap()));
+            return (file, compression_method);
+        }
+        let file = if compression_method == CompressionMethod::Identity {
+            file
+        } else if let Some(file) = self.source.get(path) {
+            file
+        } else {
+            return (file, compression_method);
+        };
+
+        let mut injected = Asset::new(live_reload::inject_script(
+            &file.data,
+            self.request.uri().path(),
+        ));
+        injected.metadata.last_modified = file.metadata.last_modified;
+        (injected, CompressionMethod::Identity)
+    }
+}
+
+impl<E, T> ServeFuture<E, T> {
+    /// Builds the response for the request.
+    ///
+    /// `None` is returned if the request should be passed to the fallback service.
+    fn respond(&self) -> Option<Response<ServeBody>> {
+        // answer a CORS preflight request, even if OPTIONS is served as GET or passed to the fallback service
+        if let Some(response) = self.cors_preflight() {
+            return Some(response);
+        }
+
+        // Accept only GET, HEAD and the configured methods
+        if !self.config.methods.contains(self.request.method()) {
+            if self.fall_through {
+                return None;
+            }
+            if self.request.method() == http::Method::OPTIONS {
+                return Some(
+                    Response::builder()
+                        .status(StatusCode::NO_CONTENT)
+                        .header(http::header::ALLOW, self.allow())
+                        .body(ServeBody::full(Bytes::new()))
+                        .unwrap(),
+                );
+            }
+            let mut headers = http::HeaderMap::new();
+            headers.insert(http::header::ALLOW, self.allow());
+            return Some(self.error_response(StatusCode::METHOD_NOT_ALLOWED, None, Some(&headers)));
+        }
+
+        // serve the live reload event stream
+        #[cfg(feature = "dev")]
+        if let Some(live_reload) = self.config.live_reload.as_ref() {
+            if self.request.uri().path().trim_start_matches('/') == live_reload::LIVE_RELOAD_PATH {
+                return Some(
+                    Response::builder()
+                        .header(http::header::CONTENT_TYPE, "text/event-stream")
+                        .header(http::header::CACHE_CONTROL, "no-cache")
+                        .body(ServeBody::live_reload(live_reload.subscribe()))
+                        .unwrap(),
+                );
+            }
         }
 
         // get embedded file for the requested path
-        let (path, file, compression_method, is_fallback) = match self.get_file_with_fallback(
-            self.request.uri().path(),
+        let request_path = path::normalize(self.request.uri().path());
+        let result = self.get_file_with_fallback(
+            request_path.as_deref(),
             &from_acceptable_encoding(
                 self.request
                     .headers()
                     .get(http::header::ACCEPT_ENCODING)
-                    .map(|x| x.to_str().ok())
-                    .flatten(),
+                    .and_then(|x| x.to_str().ok()),
             ),
-        ) {
+        );
+        let vary = |response| self.vary_navigation(result.is_fallback, response);
+        let (path, file, compression_method, is_fallback) = match result {
             // if the file is found, return it
             GetFileResult {
                 path,
@@ -376,58 +1348,104 @@ impl<E: RustEmbed, T> Future for ServeFuture<E, T> {
                 compression_method: _,
                 is_fallback,
             } => {
-                return Poll::Ready(Ok(Response::builder()
-                    .status(if is_fallback {
-                        StatusCode::TEMPORARY_REDIRECT
-                    } else {
-                        StatusCode::MOVED_PERMANENTLY
-                    })
-                    .header(http::header::LOCATION, should_redirect)
-                    .header(http::header::CONTENT_TYPE, "text/plain")
-                    .body(Full::new(if is_fallback {
-                        Bytes::from("Temporary redirect")
-                    } else {
-                        Bytes::from("Moved permanently")
-                    }))
-                    .unwrap()));
+                let status = if is_fallback {
+                    StatusCode::TEMPORARY_REDIRECT
+                } else {
+                    StatusCode::MOVED_PERMANENTLY
+                };
+                return Some(vary(self.error_response(
+                    status,
+                    Some(&should_redirect),
+                    None,
+                )));
             }
-            // if the file is not found, return 404
+            // if the file is not found, pass the request to the fallback service, or return an error response
             _ => {
-                unreachable!();
+                if self.fall_through {
+                    return None;
+                }
+                let (status, fallback_headers) = self.fallback_status(request_path.as_deref());
+                if let Some(response) = self.suggestion_redirect(status) {
+                    return Some(vary(response));
+                }
+                return Some(vary(self.error_response(status, None, fallback_headers)));
             }
         };
 
+        #[cfg(feature = "runtime-config")]
+        let (file, compression_method) =
+            self.inject_runtime_config(&path, file, compression_method);
+        #[cfg(feature = "dev")]
+        let (file, compression_method) = self.inject_live_reload(&path, file, compression_method);
+
+        let (status, fallback_headers) = if is_fallback {
+            self.fallback_status(request_path.as_deref())
+        } else {
+            (StatusCode::OK, None)
+        };
+
+        // a fallback file served with 200 is named by `Content-Location`, so that caches can tell it from the requested URL
+        let content_location =
+            if is_fallback && status == StatusCode::OK && self.source.metadata(&path).is_some() {
+                Some(path::relative(self.request.uri().path(), &path))
+            } else {
+                None
+            };
+
+        // fill the placeholders of an error page, and write the nonce into an HTML file
+        let rewritten = self.rewrite_body(&path, &file, compression_method, status);
+
         // If the client has the same file, return 304
-        if !is_fallback
-            && self
-                .request
-                .headers()
-                .get(http::header::IF_NONE_MATCH)
-                .and_then(|value| {
-                    value
-                        .to_str()
-                        .ok()
-                        .and_then(|value| Some(value.trim_matches('"')))
-                })
-                == Some(hash_to_string(&file.metadata.sha256_hash()).as_str())
-        {
-            return Poll::Ready(Ok(Response::builder()
-                .status(StatusCode::NOT_MODIFIED)
-                .body(Full::new(Bytes::from("")))
-                .unwrap()));
+        if status == StatusCode::OK && rewritten.is_none() && self.is_not_modified(&file.metadata) {
+            let mut response_builder = Response::builder().status(StatusCode::NOT_MODIFIED).header(
+                http::header::ETAG,
+                hash_to_string(&file.metadata.sha256_hash),
+            );
+            if let Some(content_location) = content_location {
+                response_builder =
+                    response_builder.header(http::header::CONTENT_LOCATION, content_location);
+            }
+            return Some(vary(
+                response_builder
+                    .body(ServeBody::full(Bytes::from("")))
+                    .unwrap(),
+            ));
+        }
+
+        if let Some(response) = self.suggestion_redirect(status) {
+            return Some(vary(response));
+        }
+        let link = self.suggestion_link(status);
+
+        let content_type = mime_guess::from_path(path.as_ref())
+            .first_or_octet_stream()
+            .to_string();
+
+        // a rewritten body is different for each request, so it has no ETag and is not stored
+        if let Some(body) = rewritten {
+            let mut response_builder = Response::builder()
+                .status(status)
+                .header(http::header::CONTENT_TYPE, content_type)
+                .header(http::header::CACHE_CONTROL, "no-store");
+            if let Some(content_location) = content_location {
+                response_builder =
+                    response_builder.header(http::header::CONTENT_LOCATION, content_location);
+            }
+            if let Some(link) = link {
+                response_builder = response_builder.header(http::header::LINK, link);
+            }
+            for (name, value) in fallback_headers.into_iter().flatten() {
+                response_builder = response_builder.header(name, value);
+            }
+            return Some(vary(response_builder.body(ServeBody::full(body)).unwrap()));
         }
 
         // build response and set headers
         let mut response_builder = Response::builder()
-            .header(
-                http::header::CONTENT_TYPE,
-                mime_guess::from_path(path.as_ref())
-                    .first_or_octet_stream()
-                    .to_string(),
-            )
+            .header(http::header::CONTENT_TYPE, content_type)
             .header(
                 http::header::ETAG,
-                hash_to_string(&file.metadata.sha256_hash()),
+                hash_to_string(&file.metadata.sha256_hash),
             );
 
         match compression_method {
@@ -444,21 +1462,474 @@ impl<E: RustEmbed, T> Future for ServeFuture<E, T> {
             }
         }
 
-        if let Some(last_modified) = file.metadata.last_modified() {
+        if let Some(last_modified) = file.metadata.last_modified {
             response_builder =
                 response_builder.header(http::header::LAST_MODIFIED, date_to_string(last_modified));
         }
 
-        if is_fallback && self.fallback_behavior != FallbackBehavior::Ok {
-            response_builder = response_builder.status(StatusCode::NOT_FOUND);
+        if let Some(content_location) = content_location {
+            response_builder =
+                response_builder.header(http::header::CONTENT_LOCATION, content_location);
+        }
+
+        if let Some(link) = link {
+            response_builder = response_builder.header(http::header::LINK, link);
+        }
+
+        for (name, value) in fallback_headers.into_iter().flatten() {
+            response_builder = response_builder.header(name, value);
+        }
+
+        response_builder = response_builder.status(status);
+
+        Some(vary(
+            response_builder.body(ServeBody::full(file.data)).unwrap(),
+        ))
+    }
+
+    /// Adds `Vary: Accept, Sec-Fetch-Mode` to a fallback response in SPA mode, because [`ServeFuture::is_navigation`]
+    /// chooses between the fallback file and `404 Not Found` by these headers.
+    fn vary_navigation(
+        &self,
+        is_fallback: bool,
+        mut response: Response<ServeBody>,
+    ) -> Response<ServeBody> {
+        if self.config.spa_mode && is_fallback {
+            response.headers_mut().insert(
+                http::header::VARY,
+                http::HeaderValue::from_static("Accept, Sec-Fetch-Mode"),
+            );
+        }
+        response
+    }
+
+    /// Returns the status code and the extra headers of a fallback response for `path`.
+    fn fallback_status(&self, path: Option<&str>) -> (StatusCode, Option<&http::HeaderMap>) {
+        if self.config.spa_mode && !self.is_navigation() {
+            return (StatusCode::NOT_FOUND, None);
+        }
+        let (_, fallback_behavior, headers) = self.fallback(path);
+        let status = match fallback_behavior {
+            FallbackBehavior::Ok => StatusCode::OK,
+            FallbackBehavior::Status(status) => status,
+            FallbackBehavior::NotFound | FallbackBehavior::Redirect => StatusCode::NOT_FOUND,
+        };
+        (status, Some(headers))
+    }
+
+    /// Builds an error response, or a redirect response to `location`, with a body in the format chosen by the `Accept` header.
+    ///
+    /// The body is the page registered by [`ServeEmbedBuilder::error_page`], or a built-in body.
+    /// The placeholders are filled if [`ServeEmbedBuilder::error_templates`] is enabled, the runtime configuration is inserted into
+    /// an HTML error page, and the nonce is written into an HTML body.
+    /// A missing file served with a status code that is not an error is described as `404 Not Found`.
+    fn error_response(
+        &self,
+        status: StatusCode,
+        location: Option<&str>,
+        headers: Option<&http::HeaderMap>,
+    ) -> Response<ServeBody> {
+        let (formats, default_format) = match (status, location) {
+            (_, Some(_)) => (
+                &[ErrorFormat::Html, ErrorFormat::Text][..],
+                ErrorFormat::Text,
+            ),
+            (StatusCode::METHOD_NOT_ALLOWED, None) => (ErrorFormat::ALL, ErrorFormat::Text),
+            _ => (ErrorFormat::ALL, ErrorFormat::Html),
+        };
+        let format = error::negotiate(
+            self.request
+                .headers()
+                .get(http::header::ACCEPT)
+                .and_then(|x| x.to_str().ok()),
+            formats,
+            default_format,
+        );
+        let body_status =
+            if location.is_none() && !status.is_client_error() && !status.is_server_error() {
+                StatusCode::NOT_FOUND
+            } else {
+                status
+            };
+        let body = self
+            .config
+            .error_pages
+            .get(&(body_status, format))
+            .and_then(|x| self.source.get(x))
+            .map(|x| x.data)
+            .unwrap_or_else(|| {
+                error::default_body(body_status, format, self.request.uri().path(), location)
+            });
+        let rendered = if self.config.error_templates {
+            error::render_template(
+                &body,
+                error::escape_for(format.content_type()),
+                &self.template_values(body_status),
+            )
         } else {
-            response_builder = response_builder.status(StatusCode::OK);
+            None
+        };
+        // insert the runtime configuration into an HTML error page, as into an HTML file
+        #[cfg(feature = "runtime-config")]
+        let rendered = match self
+            .config
+            .runtime_config
+            .as_ref()
+            .filter(|_| format == ErrorFormat::Html && location.is_none())
+        {
+            Some(runtime_config) => runtime_config
+                .inject(rendered.as_ref().unwrap_or(&body), self.config.csp_nonce)
+                .or(rendered),
+            None => rendered,
+        };
+        // write the nonce into an HTML body, as into an HTML file
+        let rendered = match self
+            .config
+            .csp_nonce
+            .filter(|_| format == ErrorFormat::Html)
+        {
+            Some(mode) => {
+                nonce::inject(rendered.as_ref().unwrap_or(&body), mode, self.nonce()).or(rendered)
+            }
+            None => rendered,
+        };
+
+        let mut response_builder = Response::builder()
+            .status(status)
+            .header(http::header::CONTENT_TYPE, format.content_type())
+            .header(http::header::VARY, "Accept");
+        if rendered.is_some() {
+            response_builder = response_builder.header(http::header::CACHE_CONTROL, "no-store");
+        }
+        if let Some(link) = location
+            .is_none()
+            .then(|| self.suggestion_link(status))
+            .flatten()
+        {
+            response_builder = response_builder.header(http::header::LINK, link);
+        }
+        if let Some(location) = location {
+            response_builder = response_builder.header(http::header::LOCATION, location);
+        }
+        for (name, value) in headers.into_iter().flatten() {
+            response_builder = response_builder.header(name, value);
+        }
+        response_builder
+            .body(ServeBody::full(rendered.unwrap_or(body)))
+            .unwrap()
+    }
+
+    /// Rewrites the body of a file for this request. `None` is returned if the file is served as it is.
+    ///
+    /// The placeholders of an error page are filled, and then the nonce is written into an HTML file.
+    fn rewrite_body(
+        &self,
+        path: &str,
+        file: &Asset,
+        compression_method: CompressionMethod,
+        status: StatusCode,
+    ) -> Option<Bytes> {
+        let rendered = self.render_error_page(path, file, compression_method, status);
+        let Some(mode) = self.config.csp_nonce.filter(|_| {
+            is_document(
+                mime_guess::from_path(path)
+                    .first_or_octet_stream()
+                    .essence_str(),
+            )
+        }) else {
+            return rendered;
+        };
+        let data = match &rendered {
+            Some(rendered) => rendered.clone(),
+            None => self.uncompressed_data(path, file, compression_method)?,
+        };
+        nonce::inject(&data, mode, self.nonce()).or(rendered)
+    }
+
+    /// Returns the uncompressed content of `file`, which is `path` compressed with `compression_method`.
+    fn uncompressed_data(
+        &self,
+        path: &str,
+        file: &Asset,
+        compression_method: CompressionMethod,
+    ) -> Option<Bytes> {
+        if compression_method == CompressionMethod::Identity {
+            Some(file.data.clone())
+        } else {
+            self.source.get(path).map(|x| x.data)
+        }
+    }
+
+    /// Returns the nonce of this request, which is generated on the first call.
+    fn nonce(&self) -> &str {
+        self.nonce.get_or_init(nonce::generate)
+    }
+
+    /// Fills the placeholders of a fallback file served with an error status code, if [`ServeEmbedBuilder::error_templates`] is enabled.
+    ///
+    /// The placeholders are filled in the uncompressed file. `None` is returned if the file is not changed.
+    fn render_error_page(
+        &self,
+        path: &str,
+        file: &Asset,
+        compression_method: CompressionMethod,
+        status: StatusCode,
+    ) -> Option<Bytes> {
+        if !self.config.error_templates || !(status.is_client_error() || status.is_server_error()) {
+            return None;
+        }
+        let data = self.uncompressed_data(path, file, compression_method)?;
+        let content_type = mime_guess::from_path(path).first_or_octet_stream();
+        error::render_template(
+            &data,
+            error::escape_for(content_type.essence_str()),
+            &self.template_values(status),
+        )
+    }
+
+    /// Returns the values of the placeholders of an error page.
+    fn template_values(&self, status: StatusCode) -> Vec<(&'static str, String)> {
+        let request_id = self
+            .request
+            .headers()
+            .get("x-request-id")
+            .and_then(|x| x.to_str().ok())
+            .unwrap_or_default();
+        let path = path::normalize(self.request.uri().path())
+            .map(|x| format!("/{}", x))
+            .unwrap_or_else(|| self.request.uri().path().to_owned());
+        let suggestions: Vec<String> = self
+            .suggestions(status)
+            .iter()
+            .map(|x| path::relative(self.request.uri().path(), &x.path))
+            .collect();
+        vec![
+            ("path", path),
+            ("status", status.as_u16().to_string()),
+            ("request_id", request_id.to_owned()),
+            (
+                "suggestion",
+                suggestions.first().cloned().unwrap_or_default(),
+            ),
+            ("suggestions", suggestions.join(", ")),
+        ]
+    }
+
+    /// Returns the files whose path is close to the requested path, if suggestions are enabled and `status` is an error.
+    fn suggestions(&self, status: StatusCode) -> &[suggest::Suggestion] {
+        if !status.is_client_error() && !status.is_server_error() {
+            return &[];
+        }
+        self.suggestions.get_or_init(|| {
+            match (
+                self.config.suggestion_index.as_ref(),
+                path::normalize(self.request.uri().path()),
+            ) {
+                (Some(index), Some(path)) => index.suggest(&path),
+                _ => Vec::new(),
+            }
+        })
+    }
+
+    /// Builds a redirect response to the only suggestion with high confidence if [`ServeEmbedBuilder::suggestion_redirect`] is enabled.
+    fn suggestion_redirect(&self, status: StatusCode) -> Option<Response<ServeBody>> {
+        if !self.config.suggestion_redirect {
+            return None;
+        }
+        let mut confident = self.suggestions(status).iter().filter(|x| x.confident);
+        match (confident.next(), confident.next()) {
+            (Some(suggestion), None) => Some(self.error_response(
+                StatusCode::TEMPORARY_REDIRECT,
+                Some(&path::relative(self.request.uri().path(), &suggestion.path)),
+                None,
+            )),
+            _ => None,
+        }
+    }
+
+    /// Builds the `Link` header of the suggestions for an error response.
+    fn suggestion_link(&self, status: StatusCode) -> Option<String> {
+        let suggestions = self.suggestions(status);
+        if suggestions.is_empty() {
+            return None;
         }
+        Some(
+            suggestions
+                .iter()
+                .map(|x| {
+                    format!(
+                        "<{}>; rel=\"alternate\"",
+                        path::relative(self.request.uri().path(), &x.path)
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join(", "),
+        )
+    }
+
+    /// Returns `true` if the client has the same file.
+    ///
+    /// `If-None-Match` is compared with the `ETag`. `If-Modified-Since` is compared with the last modified time only if there is no `If-None-Match`.
+    fn is_not_modified(&self, metadata: &AssetMetadata) -> bool {
+        let headers = self.request.headers();
+        if let Some(if_none_match) = headers.get(http::header::IF_NONE_MATCH) {
+            let etag = hash_to_string(&metadata.sha256_hash);
+            return if_none_match.to_str().is_ok_and(|value| {
+                value
+                    .split(',')
+                    .map(str::trim)
+                    .any(|x| x == "*" || x.trim_start_matches("W/").trim_matches('"') == etag)
+            });
+        }
+
+        let if_modified_since = headers
+            .get(http::header::IF_MODIFIED_SINCE)
+            .and_then(|x| x.to_str().ok())
+            .and_then(|x| DateTime::parse_from_rfc2822(x).ok());
+        match (if_modified_since, metadata.last_modified) {
+            (Some(if_modified_since), Some(last_modified)) => {
+                last_modified as i64 <= if_modified_since.timestamp()
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns the first [`CorsRule`] that matches the path of the request.
+    fn cors_rule(&self) -> Option<&CorsRule> {
+        let path = path::normalize(self.request.uri().path())?;
+        self.config.cors_rules.iter().find(|x| x.is_match(&path))
+    }
+
+    /// Builds a response to a CORS preflight request if a [`CorsRule`] matches the path of the request.
+    fn cors_preflight(&self) -> Option<Response<ServeBody>> {
+        let headers = self.request.headers();
+        if self.request.method() != http::Method::OPTIONS
+            || !headers.contains_key(http::header::ACCESS_CONTROL_REQUEST_METHOD)
+        {
+            return None;
+        }
+        let origin = headers.get(http::header::ORIGIN)?;
+        let rule = self.cors_rule()?;
+        let mut response = Response::builder()
+            .status(StatusCode::NO_CONTENT)
+            .header(http::header::ALLOW, self.allow())
+            .body(ServeBody::full(Bytes::new()))
+            .unwrap();
+        rule.apply_preflight(origin, self.allow(), response.headers_mut());
+        Some(response)
+    }
+
+    /// Returns the [`SecurityHeaders`] for the path of the request.
+    fn security_headers(&self) -> &SecurityHeaders {
+        path::normalize(self.request.uri().path())
+            .and_then(|path| {
+                self.config
+                    .security_header_rules
+                    .iter()
+                    .find(|(glob, _)| glob.is_match(&path))
+            })
+            .map(|(_, headers)| headers)
+            .unwrap_or(&self.config.security_headers)
+    }
 
-        Poll::Ready(Ok(response_builder
-            .body(Full::new(cow_to_bytes(file.data)))
-            .unwrap()))
+    /// Returns the value of the `Allow` header, such as `GET, HEAD, OPTIONS`.
+    fn allow(&self) -> http::HeaderValue {
+        let mut methods: Vec<&str> = self.config.methods.iter().map(|x| x.as_str()).collect();
+        if !self.config.methods.contains(&http::Method::OPTIONS) {
+            methods.push(http::Method::OPTIONS.as_str());
+        }
+        http::HeaderValue::from_str(&methods.join(", ")).unwrap()
+    }
+
+    /// Finishes a response with the headers common to every response.
+    ///
+    /// `Content-Length` is set for a body with a known length except for `204` and `304`, the CORS headers are set if a [`CorsRule`] matches,
+    /// the cross-origin isolation headers are set if enabled, the security headers are set, and `X-Bundle-Version` is set to the version of the bundle.
+    /// The body of a response to `HEAD` is dropped, so the headers stay the same as for `GET`.
+    fn finish_response(&self, mut response: Response<ServeBody>) -> Response<ServeBody> {
+        if response.status() != StatusCode::NOT_MODIFIED
+            && response.status() != StatusCode::NO_CONTENT
+        {
+            if let Some(length) = http_body::Body::size_hint(response.body()).exact() {
+                response
+                    .headers_mut()
+                    .insert(http::header::CONTENT_LENGTH, length.into());
+            }
+        }
+        if self.request.method() == http::Method::HEAD {
+            *response.body_mut() = ServeBody::full(Bytes::new());
+        }
+        if let Some(rule) = self.cors_rule() {
+            rule.apply(
+                self.request.headers().get(http::header::ORIGIN),
+                response.headers_mut(),
+            );
+        }
+        let mime = mime_type(response.headers());
+        if self.config.cross_origin_isolation {
+            set_cross_origin_isolation(&mime, response.headers_mut());
+        }
+        let nonce = self
+            .config
+            .csp_nonce
+            .filter(|_| is_document(&mime))
+            .map(|_| self.nonce());
+        self.security_headers()
+            .apply(is_document(&mime), nonce, response.headers_mut());
+        if let Ok(version) = http::HeaderValue::from_str(self.config.version(self.source.as_ref()))
+        {
+            response
+                .headers_mut()
+                .insert(http::HeaderName::from_static("x-bundle-version"), version);
+        }
+        response
+    }
+}
+
+impl<E, T> Future for ServeFuture<E, T> {
+    type Output = Result<Response<ServeBody>, Infallible>;
+
+    fn poll(self: Pin<&mut Self>, _cx: &mut std::task::Context<'_>) -> Poll<Self::Output> {
+        // without a fallback service, the fallback file or the default 404 page is always served
+        let response = self.respond().unwrap();
+        Poll::Ready(Ok(self.finish_response(response)))
+    }
+}
+
+/// Returns the MIME type of the `Content-Type` of a response without parameters, such as `"text/html"`.
+fn mime_type(headers: &http::HeaderMap) -> String {
+    headers
+        .get(http::header::CONTENT_TYPE)
+        .and_then(|x| x.to_str().ok())
+        .and_then(|x| x.split(';').next())
+        .map(|x| x.trim().to_ascii_lowercase())
+        .unwrap_or_default()
+}
+
+/// Returns `true` if `mime` is the MIME type of an HTML document.
+fn is_document(mime: &str) -> bool {
+    mime == "text/html" || mime == "application/xhtml+xml"
+}
+
+/// Sets the cross-origin isolation headers by `mime`, the MIME type of a response.
+fn set_cross_origin_isolation(mime: &str, headers: &mut http::HeaderMap) {
+    let is_document = is_document(mime);
+    if is_document {
+        headers.insert(
+            http::HeaderName::from_static("cross-origin-opener-policy"),
+            http::HeaderValue::from_static("same-origin"),
+        );
+    }
+    if is_document || mime.ends_with("javascript") {
+        headers.insert(
+            http::HeaderName::from_static("cross-origin-embedder-policy"),
+            http::HeaderValue::from_static("require-corp"),
+        );
     }
+    headers.insert(
+        http::HeaderName::from_static("cross-origin-resource-policy"),
+        http::HeaderValue::from_static("same-origin"),
+    );
 }
 
 fn hash_to_string(hash: &[u8; 32]) -> String {
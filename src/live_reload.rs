@@ -0,0 +1,185 @@
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    convert::Infallible,
+    hash::{Hash, Hasher},
+    path::{Path, PathBuf},
+    pin::Pin,
+    sync::{Arc, Mutex, Weak},
+    task::{Poll, Waker},
+    time::Duration,
+};
+
+use bytes::Bytes;
+use http_body::{Body, Frame};
+
+/// The path of the Server-Sent Events endpoint, relative to the root of the service.
+pub(crate) const LIVE_RELOAD_PATH: &str = "__axum_embed/live-reload";
+
+/// How often the watched folder is scanned for changes.
+const WATCH_INTERVAL: Duration = Duration::from_millis(300);
+
+/// [`LiveReload`] notifies connected browsers when a file in the watched folder is changed.
+///
+/// A background thread scans the folder and stops when the `LiveReload` is dropped.
+pub(crate) struct LiveReload {
+    state: Mutex<State>,
+}
+
+#[derive(Default)]
+struct State {
+    version: u64,
+    next_id: u64,
+    wakers: HashMap<u64, Waker>,
+}
+
+impl std::fmt::Debug for LiveReload {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LiveReload").finish_non_exhaustive()
+    }
+}
+
+impl LiveReload {
+    /// Creates a new `LiveReload` and starts watching `folder`.
+    pub(crate) fn watch(folder: PathBuf) -> Arc<Self> {
+        let live_reload = Arc::new(Self {
+            state: Mutex::new(State::default()),
+        });
+        let weak = Arc::downgrade(&live_reload);
+        std::thread::Builder::new()
+            .name("axum-embed-watch".to_owned())
+            .spawn(move || watch_folder(&folder, weak))
+            .expect("failed to spawn the folder watcher thread");
+        live_reload
+    }
+
+    /// Tells every connected browser to reload.
+    pub(crate) fn notify(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.version += 1;
+        for (_, waker) in state.wakers.drain() {
+            waker.wake();
+        }
+    }
+
+    /// Creates a new event stream for a browser.
+    pub(crate) fn subscribe(self: &Arc<Self>) -> LiveReloadEvents {
+        let mut state = self.state.lock().unwrap();
+        let id = state.next_id;
+        state.next_id += 1;
+        LiveReloadEvents {
+            live_reload: self.clone(),
+            id,
+            version: state.version,
+            connected: false,
+        }
+    }
+}
+
+fn watch_folder(folder: &Path, live_reload: Weak<LiveReload>) {
+    let mut last_fingerprint = fingerprint(folder);
+    loop {
+        std::thread::sleep(WATCH_INTERVAL);
+        let Some(live_reload) = live_reload.upgrade() else {
+            return;
+        };
+        let current_fingerprint = fingerprint(folder);
+        if current_fingerprint != last_fingerprint {
+            last_fingerprint = current_fingerprint;
+            live_reload.notify();
+        }
+    }
+}
+
+/// Computes a hash of the path, size and modified time of every file under `folder`.
+fn fingerprint(folder: &Path) -> u64 {
+    let mut paths = Vec::new();
+    crate::dir::walk(folder, "", &mut paths);
+    paths.sort();
+
+    let mut hasher = DefaultHasher::new();
+    for path in paths {
+        path.hash(&mut hasher);
+        if let Ok(metadata) = std::fs::metadata(folder.join(&path)) {
+            metadata.len().hash(&mut hasher);
+            metadata.modified().ok().hash(&mut hasher);
+        }
+    }
+    hasher.finish()
+}
+
+/// [`LiveReloadEvents`] is a Server-Sent Events stream that sends a `reload` event whenever a watched file is changed.
+pub(crate) struct LiveReloadEvents {
+    live_reload: Arc<LiveReload>,
+    id: u64,
+    version: u64,
+    connected: bool,
+}
+
+impl std::fmt::Debug for LiveReloadEvents {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LiveReloadEvents")
+            .field("version", &self.version)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Body for LiveReloadEvents {
+    type Data = Bytes;
+    type Error = Infallible;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = self.get_mut();
+        if !this.connected {
+            this.connected = true;
+            return Poll::Ready(Some(Ok(Frame::data(Bytes::from_static(
+                b"retry: 1000\n\n",
+            )))));
+        }
+
+        let mut state = this.live_reload.state.lock().unwrap();
+        if state.version != this.version {
+            this.version = state.version;
+            return Poll::Ready(Some(Ok(Frame::data(Bytes::from_static(
+                b"data: reload\n\n",
+            )))));
+        }
+        state.wakers.insert(this.id, cx.waker().clone());
+        Poll::Pending
+    }
+
+    fn is_end_stream(&self) -> bool {
+        false
+    }
+}
+
+impl Drop for LiveReloadEvents {
+    fn drop(&mut self) {
+        if let Ok(mut state) = self.live_reload.state.lock() {
+            state.wakers.remove(&self.id);
+        }
+    }
+}
+
+/// Inserts the live reload script into an HTML document, before `</body>` if it exists.
+///
+/// `request_path` is the path of the request. It is used to build a URL to the event stream that is relative to the document,
+/// so that the script works when the service is nested under a prefix.
+pub(crate) fn inject_script(html: &[u8], request_path: &str) -> Vec<u8> {
+    let script = format!(
+        "<script>new EventSource(\"{}\").onmessage=function(){{location.reload()}};</script>",
+        crate::path::relative(request_path, LIVE_RELOAD_PATH)
+    );
+
+    let position = html
+        .windows(7)
+        .rposition(|x| x.eq_ignore_ascii_case(b"</body>"))
+        .unwrap_or(html.len());
+    let mut injected = Vec::with_capacity(html.len() + script.len());
+    injected.extend_from_slice(&html[..position]);
+    injected.extend_from_slice(script.as_bytes());
+    injected.extend_from_slice(&html[position..]);
+    injected
+}
@@ -0,0 +1,146 @@
+use bytes::Bytes;
+
+/// The placeholder that is replaced with the nonce in HTML files and in the `Content-Security-Policy` header.
+pub(crate) const PLACEHOLDER: &str = "{{csp_nonce}}";
+
+/// [`CspNonce`] is an enumeration of the ways to write a per-request nonce into the HTML files.
+///
+/// A fresh random nonce is generated for each request, and replaces `{{csp_nonce}}` in the `Content-Security-Policy`
+/// header of [`SecurityHeaders`](crate::SecurityHeaders), such as `script-src 'nonce-{{csp_nonce}}'`.
+/// A rewritten HTML file is served with `Cache-Control: no-store` and without `ETag`, because the nonce must not be reused.
+///
+/// # Example
+/// ```ignore
+/// let serve_app = ServeEmbed::<Assets>::builder()
+///     .security_headers(SecurityHeaders::new().content_security_policy(HeaderValue::from_static(
+///         "default-src 'self'; script-src 'nonce-{{csp_nonce}}'; style-src 'nonce-{{csp_nonce}}'",
+///     )))
+///     .csp_nonce(CspNonce::Tags)
+///     .build();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CspNonce {
+    /// Replaces every `{{csp_nonce}}` in HTML files, such as `<script nonce="{{csp_nonce}}">`.
+    Placeholder,
+    /// Adds a `nonce` attribute to every `<script>` and `<style>` tag in HTML files. Tags that already have a `nonce`
+    /// attribute, and text inside scripts, styles and comments are left unchanged.
+    Tags,
+}
+
+/// Generates a random nonce of 128 bits, encoded in hex.
+pub(crate) fn generate() -> String {
+    let mut bytes = [0u8; 16];
+    getrandom::getrandom(&mut bytes).expect("failed to generate a random nonce");
+    bytes.iter().map(|x| format!("{:02x}", x)).collect()
+}
+
+/// Writes `nonce` into `html`. `None` is returned if nothing is changed.
+pub(crate) fn inject(html: &[u8], mode: CspNonce, nonce: &str) -> Option<Bytes> {
+    let mut injected = Vec::with_capacity(html.len() + 64);
+    let mut rest = html;
+    let mut changed = false;
+    loop {
+        let found = match mode {
+            CspNonce::Placeholder => find(rest, PLACEHOLDER.as_bytes()).map(|x| {
+                (
+                    x,
+                    x + PLACEHOLDER.len(),
+                    x + PLACEHOLDER.len(),
+                    nonce.to_owned(),
+                )
+            }),
+            CspNonce::Tags => {
+                find_tag(rest).map(|(x, end)| (x, x, end, format!(" nonce=\"{}\"", nonce)))
+            }
+        };
+        let Some((start, replaced_end, end, replacement)) = found else {
+            break;
+        };
+        injected.extend_from_slice(&rest[..start]);
+        injected.extend_from_slice(replacement.as_bytes());
+        injected.extend_from_slice(&rest[replaced_end..end]);
+        rest = &rest[end..];
+        changed = true;
+    }
+    injected.extend_from_slice(rest);
+    changed.then(|| injected.into())
+}
+
+fn find(data: &[u8], needle: &[u8]) -> Option<usize> {
+    data.windows(needle.len()).position(|x| x == needle)
+}
+
+fn find_ignore_ascii_case(data: &[u8], needle: &[u8]) -> Option<usize> {
+    data.windows(needle.len())
+        .position(|x| x.eq_ignore_ascii_case(needle))
+}
+
+/// Returns the position just after the name of the first `<script` or `<style` tag without a `nonce` attribute,
+/// and the position after its content to resume scanning from.
+///
+/// The content of `<script>` and `<style>` elements and HTML comments are skipped, so that text such as
+/// `'<script>'` in JavaScript is not rewritten.
+fn find_tag(data: &[u8]) -> Option<(usize, usize)> {
+    let mut index = 0;
+    while index < data.len() {
+        if data[index..].starts_with(b"<!--") {
+            index = find(&data[index + 4..], b"-->").map_or(data.len(), |x| index + 4 + x + 3);
+            continue;
+        }
+        let Some(tag) = [&b"script"[..], &b"style"[..]]
+            .into_iter()
+            .find(|tag| is_tag(data, index, tag))
+        else {
+            index += 1;
+            continue;
+        };
+        let name_end = index + 1 + tag.len();
+        let tag_end = find_tag_end(data, name_end);
+        let close_tag = [&b"</"[..], tag].concat();
+        let content_end = find_ignore_ascii_case(&data[tag_end..], &close_tag)
+            .map_or(data.len(), |x| tag_end + x);
+        if !has_nonce(&data[name_end..tag_end]) {
+            return Some((name_end, content_end));
+        }
+        index = content_end;
+    }
+    None
+}
+
+/// Returns `true` if the `<tag` start tag begins at `index`.
+fn is_tag(data: &[u8], index: usize, tag: &[u8]) -> bool {
+    let end = index + 1 + tag.len();
+    data[index] == b'<'
+        && data
+            .get(index + 1..end)
+            .is_some_and(|x| x.eq_ignore_ascii_case(tag))
+        && data
+            .get(end)
+            .is_some_and(|x| x.is_ascii_whitespace() || *x == b'>' || *x == b'/')
+}
+
+/// Returns the position just after the `>` that closes the tag whose attributes start at `index`.
+fn find_tag_end(data: &[u8], index: usize) -> usize {
+    let mut quote = None;
+    for (i, x) in data.iter().enumerate().skip(index) {
+        match (quote, *x) {
+            (None, b'"' | b'\'') => quote = Some(*x),
+            (Some(q), x) if q == x => quote = None,
+            (None, b'>') => return i + 1,
+            _ => {}
+        }
+    }
+    data.len()
+}
+
+/// Returns `true` if `attributes` has a `nonce` attribute.
+fn has_nonce(attributes: &[u8]) -> bool {
+    attributes.windows(6).enumerate().any(|(i, x)| {
+        x[0].is_ascii_whitespace()
+            && x[1..].eq_ignore_ascii_case(b"nonce")
+            && attributes[i + 6..]
+                .iter()
+                .find(|x| !x.is_ascii_whitespace())
+                .is_some_and(|x| *x == b'=')
+    })
+}
@@ -0,0 +1,86 @@
+use std::{borrow::Cow, collections::HashSet, sync::Arc};
+
+use rust_embed::RustEmbed;
+
+use crate::{source::Embedded, Asset, AssetMetadata, AssetSource, CompressionMethod};
+
+/// [`OverlaySource`] is an [`AssetSource`] that merges several sources in priority order.
+///
+/// For each path, the layers are checked in the order they were added, and the first layer that has the file wins.
+/// A file in an override layer shadows the file at the same path in the layers below it.
+/// Because [`ServeEmbed`](crate::ServeEmbed) resolves index files and fallback files through the source, they are looked up in the merged view.
+///
+/// A compressed sidecar file is taken from the same layer as the uncompressed file,
+/// so an outdated `app.js.br` in a base layer is never served for an `app.js` in an override layer.
+///
+/// # Example
+/// ```ignore
+/// let source = OverlaySource::new()
+///     .embed::<ProductOverrides>()
+///     .embed::<BaseUi>();
+/// let serve_assets = ServeEmbed::from_source(source);
+/// ```
+#[derive(Clone, Default)]
+pub struct OverlaySource {
+    layers: Vec<Arc<dyn AssetSource>>,
+}
+
+impl std::fmt::Debug for OverlaySource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OverlaySource")
+            .field("layers", &self.layers.len())
+            .finish()
+    }
+}
+
+impl OverlaySource {
+    /// Creates an empty `OverlaySource`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a [`RustEmbed`] type as a layer with lower priority than the layers already added.
+    pub fn embed<E: RustEmbed + 'static>(self) -> Self {
+        self.layer(Embedded::<E>::new())
+    }
+
+    /// Adds an [`AssetSource`] as a layer with lower priority than the layers already added.
+    pub fn layer(mut self, source: impl AssetSource) -> Self {
+        self.layers.push(Arc::new(source));
+        self
+    }
+
+    pub(crate) fn shared_layer(mut self, source: Arc<dyn AssetSource>) -> Self {
+        self.layers.push(source);
+        self
+    }
+
+    /// Returns the first layer that has the file at `path`.
+    fn find_layer(&self, path: &str) -> Option<&Arc<dyn AssetSource>> {
+        self.layers.iter().find(|x| x.metadata(path).is_some())
+    }
+}
+
+impl AssetSource for OverlaySource {
+    fn get(&self, path: &str) -> Option<Asset> {
+        self.layers.iter().find_map(|x| x.get(path))
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = Cow<'static, str>> + '_> {
+        let mut found = HashSet::new();
+        Box::new(
+            self.layers
+                .iter()
+                .flat_map(|x| x.iter())
+                .filter(move |x| found.insert(x.clone())),
+        )
+    }
+
+    fn metadata(&self, path: &str) -> Option<AssetMetadata> {
+        self.layers.iter().find_map(|x| x.metadata(path))
+    }
+
+    fn get_compressed(&self, path: &str, method: CompressionMethod) -> Option<Asset> {
+        self.find_layer(path)?.get_compressed(path, method)
+    }
+}
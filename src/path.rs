@@ -0,0 +1,65 @@
+/// Decodes and normalizes the path of a request into a relative path from the root of the source.
+///
+/// Each segment is percent-decoded, empty segments and `.` are removed, and `..` removes the previous segment.
+/// A trailing slash is kept so that directories can be told apart from files.
+/// `None` is returned if the path goes above the root, or if a decoded segment is not valid UTF-8 or contains a slash, a backslash or a NUL character.
+pub(crate) fn normalize(path: &str) -> Option<String> {
+    let mut segments: Vec<String> = Vec::new();
+    for segment in path.split('/') {
+        let segment = decode(segment)?;
+        if segment.contains(['/', '\\', '\0']) {
+            return None;
+        }
+        match segment.as_str() {
+            "" | "." => {}
+            ".." => {
+                segments.pop()?;
+            }
+            _ => segments.push(segment),
+        }
+    }
+
+    let mut normalized = segments.join("/");
+    if !normalized.is_empty() && (path.ends_with('/') || path.ends_with("/.")) {
+        normalized.push('/');
+    }
+    Some(normalized)
+}
+
+fn decode(segment: &str) -> Option<String> {
+    let bytes = segment.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        if bytes[index] == b'%' {
+            let hex = std::str::from_utf8(bytes.get(index + 1..index + 3)?).ok()?;
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            index += 3;
+        } else {
+            decoded.push(bytes[index]);
+            index += 1;
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
+
+/// Percent-encodes a normalized path so that it can be used in a URL, such as the `Location` header.
+pub(crate) fn encode(path: &str) -> String {
+    let mut encoded = String::with_capacity(path.len());
+    for byte in path.bytes() {
+        if byte.is_ascii_alphanumeric() || b"/-._~!$&'()*+,;=:@".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+/// Builds a relative URL from the document at `request_path` to `path`, a normalized path relative to the root.
+///
+/// The URL works when the service is nested under a prefix.
+pub(crate) fn relative(request_path: &str, path: &str) -> String {
+    let depth = request_path.trim_start_matches('/').matches('/').count();
+    format!("{}{}", "../".repeat(depth), encode(path))
+}
@@ -0,0 +1,116 @@
+use std::sync::Arc;
+
+use bytes::Bytes;
+
+use crate::{nonce, CspNonce};
+
+/// The marker comment that is replaced with the configuration script. The script is inserted before `</head>` if there is no marker,
+/// or at the start of the document, after `<!DOCTYPE html>`, if there is no `</head>` either.
+pub(crate) const MARKER: &str = "<!-- runtime-config -->";
+
+/// A value injected into HTML files as `window.__CONFIG__`, serialized to JSON for each request.
+#[derive(Clone)]
+pub(crate) struct RuntimeConfig {
+    value: Arc<dyn Fn() -> serde_json::Result<String> + Send + Sync>,
+}
+
+impl std::fmt::Debug for RuntimeConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RuntimeConfig").finish_non_exhaustive()
+    }
+}
+
+impl RuntimeConfig {
+    /// Creates a configuration that injects `value`.
+    pub(crate) fn value<V>(value: V) -> Self
+    where
+        V: serde::Serialize + Send + Sync + 'static,
+    {
+        Self {
+            value: Arc::new(move || serde_json::to_string(&value)),
+        }
+    }
+
+    /// Creates a configuration that injects the value returned by `f`, which is called for each request.
+    pub(crate) fn from_fn<F, V>(f: F) -> Self
+    where
+        F: Fn() -> V + Send + Sync + 'static,
+        V: serde::Serialize,
+    {
+        Self {
+            value: Arc::new(move || serde_json::to_string(&f())),
+        }
+    }
+
+    /// Inserts the configuration script into `html`. `None` is returned if the value cannot be serialized.
+    ///
+    /// With [`CspNonce::Placeholder`], the script has a `nonce` attribute with the placeholder, which is filled later
+    /// with the nonce of the request. With [`CspNonce::Tags`], the attribute is added later like for any other script.
+    pub(crate) fn inject(&self, html: &[u8], csp_nonce: Option<CspNonce>) -> Option<Bytes> {
+        let json = (self.value)().ok()?;
+        let nonce_attribute = match csp_nonce {
+            Some(CspNonce::Placeholder) => format!(" nonce=\"{}\"", nonce::PLACEHOLDER),
+            Some(CspNonce::Tags) | None => String::new(),
+        };
+        let script = format!(
+            "<script{}>window.__CONFIG__={};</script>",
+            nonce_attribute,
+            escape(&json)
+        );
+        let (start, end) = match find(html, MARKER.as_bytes()) {
+            Some(start) => (start, start + MARKER.len()),
+            None => {
+                let start = html
+                    .windows(b"</head>".len())
+                    .position(|x| x.eq_ignore_ascii_case(b"</head>"))
+                    .unwrap_or_else(|| doctype_end(html));
+                (start, start)
+            }
+        };
+        let mut injected = Vec::with_capacity(html.len() + script.len());
+        injected.extend_from_slice(&html[..start]);
+        injected.extend_from_slice(script.as_bytes());
+        injected.extend_from_slice(&html[end..]);
+        Some(injected.into())
+    }
+}
+
+fn find(data: &[u8], needle: &[u8]) -> Option<usize> {
+    data.windows(needle.len()).position(|x| x == needle)
+}
+
+/// Returns the position just after `<!DOCTYPE ...>` at the start of `html`, or `0` if there is none.
+///
+/// A script must not be inserted before the doctype, because the browser would render the document in quirks mode.
+fn doctype_end(html: &[u8]) -> usize {
+    let start = html.len() - html.trim_ascii_start().len();
+    let is_doctype = html
+        .get(start..start + b"<!doctype".len())
+        .is_some_and(|x| x.eq_ignore_ascii_case(b"<!doctype"));
+    if !is_doctype {
+        return 0;
+    }
+    html[start..]
+        .iter()
+        .position(|x| *x == b'>')
+        .map_or(0, |x| start + x + 1)
+}
+
+/// Escapes JSON so that it can be embedded in a `<script>` element.
+///
+/// `<`, `>` and `&` can only appear in JSON strings, where `\uXXXX` escapes keep their meaning, so a value cannot close the element.
+/// U+2028 and U+2029 are also escaped for old JavaScript engines.
+fn escape(json: &str) -> String {
+    let mut escaped = String::with_capacity(json.len());
+    for c in json.chars() {
+        match c {
+            '<' => escaped.push_str("\\u003c"),
+            '>' => escaped.push_str("\\u003e"),
+            '&' => escaped.push_str("\\u0026"),
+            '\u{2028}' => escaped.push_str("\\u2028"),
+            '\u{2029}' => escaped.push_str("\\u2029"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,117 @@
+use http::{header, HeaderMap, HeaderValue};
+
+/// [`SecurityHeaders`] is a set of security headers added to the responses of [`ServeEmbed`](crate::ServeEmbed).
+///
+/// `X-Content-Type-Options: nosniff` is added to every response by default. `Content-Security-Policy`, `Referrer-Policy`,
+/// `Permissions-Policy` and `X-Frame-Options` are added only to HTML documents, because browsers ignore them for other files.
+///
+/// The headers are set with [`ServeEmbedBuilder::security_headers`](crate::ServeEmbedBuilder::security_headers),
+/// and can be replaced for a part of the path space with [`ServeEmbedBuilder::security_headers_for`](crate::ServeEmbedBuilder::security_headers_for).
+///
+/// # Example
+/// ```ignore
+/// let serve_assets = ServeEmbed::<Assets>::builder()
+///     .security_headers(
+///         SecurityHeaders::new()
+///             .content_security_policy(HeaderValue::from_static("default-src 'self'"))
+///             .referrer_policy(HeaderValue::from_static("strict-origin-when-cross-origin"))
+///             .frame_options(HeaderValue::from_static("DENY")),
+///     )
+///     .security_headers_for("embed/**", SecurityHeaders::new())
+///     .build();
+/// ```
+#[derive(Debug, Clone)]
+pub struct SecurityHeaders {
+    nosniff: bool,
+    content_security_policy: Option<HeaderValue>,
+    referrer_policy: Option<HeaderValue>,
+    permissions_policy: Option<HeaderValue>,
+    frame_options: Option<HeaderValue>,
+}
+
+impl Default for SecurityHeaders {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SecurityHeaders {
+    /// Creates a set with only `X-Content-Type-Options: nosniff`.
+    pub fn new() -> Self {
+        Self {
+            nosniff: true,
+            content_security_policy: None,
+            referrer_policy: None,
+            permissions_policy: None,
+            frame_options: None,
+        }
+    }
+
+    /// Adds `X-Content-Type-Options: nosniff` to every response. The default is `true`.
+    pub fn nosniff(mut self, enabled: bool) -> Self {
+        self.nosniff = enabled;
+        self
+    }
+
+    /// Sets `Content-Security-Policy` for HTML documents, such as `default-src 'self'`.
+    pub fn content_security_policy(mut self, value: HeaderValue) -> Self {
+        self.content_security_policy = Some(value);
+        self
+    }
+
+    /// Sets `Referrer-Policy` for HTML documents, such as `no-referrer`.
+    pub fn referrer_policy(mut self, value: HeaderValue) -> Self {
+        self.referrer_policy = Some(value);
+        self
+    }
+
+    /// Sets `Permissions-Policy` for HTML documents, such as `camera=(), microphone=()`.
+    pub fn permissions_policy(mut self, value: HeaderValue) -> Self {
+        self.permissions_policy = Some(value);
+        self
+    }
+
+    /// Sets `X-Frame-Options` for HTML documents, such as `DENY` or `SAMEORIGIN`.
+    pub fn frame_options(mut self, value: HeaderValue) -> Self {
+        self.frame_options = Some(value);
+        self
+    }
+
+    /// Adds the headers to a response. `is_document` is `true` for an HTML document.
+    ///
+    /// `nonce` replaces the `{{csp_nonce}}` placeholders in `Content-Security-Policy`. Please see [`CspNonce`](crate::CspNonce).
+    pub(crate) fn apply(&self, is_document: bool, nonce: Option<&str>, headers: &mut HeaderMap) {
+        if self.nosniff {
+            headers.insert(
+                header::X_CONTENT_TYPE_OPTIONS,
+                HeaderValue::from_static("nosniff"),
+            );
+        }
+        if !is_document {
+            return;
+        }
+        let content_security_policy = match (&self.content_security_policy, nonce) {
+            (Some(value), Some(nonce)) => value
+                .to_str()
+                .ok()
+                .and_then(|x| {
+                    HeaderValue::from_str(&x.replace(crate::nonce::PLACEHOLDER, nonce)).ok()
+                })
+                .or(Some(value.clone())),
+            (value, _) => value.clone(),
+        };
+        for (name, value) in [
+            (header::CONTENT_SECURITY_POLICY, &content_security_policy),
+            (header::REFERRER_POLICY, &self.referrer_policy),
+            (
+                http::HeaderName::from_static("permissions-policy"),
+                &self.permissions_policy,
+            ),
+            (header::X_FRAME_OPTIONS, &self.frame_options),
+        ] {
+            if let Some(value) = value {
+                headers.insert(name, value.clone());
+            }
+        }
+    }
+}
@@ -0,0 +1,343 @@
+use std::{borrow::Cow, collections::HashMap, sync::Arc};
+
+use bytes::Bytes;
+use rust_embed::RustEmbed;
+use sha2::{Digest, Sha256};
+
+use crate::{CompressionMethod, Glob};
+
+/// [`AssetSource`] is a trait for a collection of files that can be served by [`ServeEmbed`](crate::ServeEmbed).
+///
+/// Every type that implements [`RustEmbed`] implements this trait, so a `#[derive(RustEmbed)]` struct can be served directly.
+/// Implement this trait to serve files from another place, such as an `include_dir!` bundle, a map built at runtime or generated content.
+/// Index file, fallback, compression and caching are handled by [`ServeEmbed`](crate::ServeEmbed) in the same way for every source.
+///
+/// # Example
+/// ```ignore
+/// struct Generated;
+///
+/// impl AssetSource for Generated {
+///     fn get(&self, path: &str) -> Option<Asset> {
+///         (path == "version.txt").then(|| Asset::new(env!("CARGO_PKG_VERSION")))
+///     }
+///
+///     fn iter(&self) -> Box<dyn Iterator<Item = Cow<'static, str>> + '_> {
+///         Box::new(std::iter::once(Cow::Borrowed("version.txt")))
+///     }
+/// }
+///
+/// let serve_generated = ServeEmbed::from_source(Generated);
+/// ```
+pub trait AssetSource: Send + Sync + 'static {
+    /// Returns the file at `path`, or `None` if the file does not exist.
+    ///
+    /// `path` is a relative path from the root of the source without a leading slash, such as `"images/logo.svg"`.
+    fn get(&self, path: &str) -> Option<Asset>;
+
+    /// Iterates over the paths of all files in the source.
+    fn iter(&self) -> Box<dyn Iterator<Item = Cow<'static, str>> + '_>;
+
+    /// Returns the metadata of the file at `path`, or `None` if the file does not exist.
+    ///
+    /// The default implementation calls [`AssetSource::get`]. Override it if the metadata can be obtained without loading the file.
+    fn metadata(&self, path: &str) -> Option<AssetMetadata> {
+        self.get(path).map(|x| x.metadata)
+    }
+
+    /// Returns the file at `path` compressed with `method`, or `None` if the compressed file is not available.
+    ///
+    /// The default implementation looks for a sidecar file, such as `app.js.br` for `app.js` and [`CompressionMethod::Brotli`].
+    /// Override it if the source can provide compressed data in another way.
+    fn get_compressed(&self, path: &str, method: CompressionMethod) -> Option<Asset> {
+        self.get(&format!("{}{}", path, method.extension()))
+    }
+}
+
+/// [`Asset`] is a file returned by an [`AssetSource`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Asset {
+    /// The content of the file.
+    pub data: Bytes,
+    /// The metadata of the file.
+    pub metadata: AssetMetadata,
+}
+
+impl Asset {
+    /// Creates a new `Asset` from its content. The SHA-256 hash and the size are computed from `data`.
+    pub fn new(data: impl Into<Bytes>) -> Self {
+        let data = data.into();
+        Self {
+            metadata: AssetMetadata {
+                sha256_hash: Sha256::digest(&data).into(),
+                last_modified: None,
+                size: data.len() as u64,
+            },
+            data,
+        }
+    }
+
+    /// Sets the last modified time of the file as seconds since the UNIX epoch.
+    pub fn with_last_modified(mut self, last_modified: u64) -> Self {
+        self.metadata.last_modified = Some(last_modified);
+        self
+    }
+}
+
+impl From<rust_embed::EmbeddedFile> for Asset {
+    fn from(file: rust_embed::EmbeddedFile) -> Self {
+        let data = match file.data {
+            Cow::Borrowed(x) => Bytes::from(x),
+            Cow::Owned(x) => Bytes::from(x),
+        };
+        Self {
+            metadata: AssetMetadata {
+                sha256_hash: file.metadata.sha256_hash(),
+                last_modified: file.metadata.last_modified(),
+                size: data.len() as u64,
+            },
+            data,
+        }
+    }
+}
+
+/// [`AssetMetadata`] holds the metadata of an [`Asset`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AssetMetadata {
+    /// The SHA-256 hash of the file. It is used as the `ETag` of the response.
+    pub sha256_hash: [u8; 32],
+    /// The last modified time of the file as seconds since the UNIX epoch, if known.
+    pub last_modified: Option<u64>,
+    /// The size of the file in bytes.
+    pub size: u64,
+}
+
+impl<E: RustEmbed + Send + Sync + 'static> AssetSource for E {
+    fn get(&self, path: &str) -> Option<Asset> {
+        <E as RustEmbed>::get(path).map(Asset::from)
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = Cow<'static, str>> + '_> {
+        Box::new(<E as RustEmbed>::iter())
+    }
+}
+
+/// An [`AssetSource`] for a [`RustEmbed`] type that is used without an instance of the type.
+pub(crate) struct Embedded<E>(std::marker::PhantomData<fn() -> E>);
+
+impl<E> Embedded<E> {
+    pub(crate) fn new() -> Self {
+        Self(std::marker::PhantomData)
+    }
+}
+
+impl<E: RustEmbed + 'static> AssetSource for Embedded<E> {
+    fn get(&self, path: &str) -> Option<Asset> {
+        E::get(path).map(Asset::from)
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = Cow<'static, str>> + '_> {
+        Box::new(E::iter())
+    }
+}
+
+/// An [`AssetSource`] that serves the files under a folder of another source, as if the folder were the root.
+pub(crate) struct Subdir {
+    source: Arc<dyn AssetSource>,
+    prefix: String,
+}
+
+impl Subdir {
+    pub(crate) fn new(source: Arc<dyn AssetSource>, root: &str) -> Self {
+        Self {
+            source,
+            prefix: format!("{}/", root),
+        }
+    }
+}
+
+impl AssetSource for Subdir {
+    fn get(&self, path: &str) -> Option<Asset> {
+        self.source.get(&format!("{}{}", self.prefix, path))
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = Cow<'static, str>> + '_> {
+        Box::new(self.source.iter().filter_map(|x| {
+            x.strip_prefix(self.prefix.as_str())
+                .map(|x| Cow::Owned(x.to_owned()))
+        }))
+    }
+
+    fn metadata(&self, path: &str) -> Option<AssetMetadata> {
+        self.source.metadata(&format!("{}{}", self.prefix, path))
+    }
+
+    fn get_compressed(&self, path: &str, method: CompressionMethod) -> Option<Asset> {
+        self.source
+            .get_compressed(&format!("{}{}", self.prefix, path), method)
+    }
+}
+
+/// An [`AssetSource`] that hides the files of another source that are not allowed by glob filters.
+pub(crate) struct Filtered {
+    source: Arc<dyn AssetSource>,
+    include: Vec<Glob>,
+    exclude: Vec<Glob>,
+}
+
+impl Filtered {
+    pub(crate) fn new(
+        source: Arc<dyn AssetSource>,
+        include: Vec<Glob>,
+        exclude: Vec<Glob>,
+    ) -> Self {
+        Self {
+            source,
+            include,
+            exclude,
+        }
+    }
+
+    /// Returns `true` if `path` matches one of the include patterns, or there is no include pattern,
+    /// and `path` does not match any of the exclude patterns.
+    fn is_allowed(&self, path: &str) -> bool {
+        (self.include.is_empty() || self.include.iter().any(|x| x.is_match(path)))
+            && !self.is_excluded(path)
+    }
+
+    /// Returns `true` if `path` matches one of the exclude patterns.
+    fn is_excluded(&self, path: &str) -> bool {
+        self.exclude.iter().any(|x| x.is_match(path))
+    }
+
+    /// Returns `true` if `path` can be requested directly.
+    ///
+    /// A sidecar compressed file, such as `app.js.br`, whose uncompressed file exists is allowed only if
+    /// the uncompressed file is allowed and the sidecar file itself is not excluded. Other files, such as
+    /// `archive.tar.gz` without `archive.tar`, are matched by their own name.
+    fn is_request_allowed(&self, path: &str) -> bool {
+        let uncompressed_path = [
+            CompressionMethod::Brotli,
+            CompressionMethod::Gzip,
+            CompressionMethod::Zlib,
+        ]
+        .iter()
+        .filter_map(|x| path.strip_suffix(x.extension()))
+        .find(|x| self.source.metadata(x).is_some());
+        match uncompressed_path {
+            Some(uncompressed_path) => {
+                self.is_allowed(uncompressed_path) && !self.is_excluded(path)
+            }
+            None => self.is_allowed(path),
+        }
+    }
+}
+
+impl AssetSource for Filtered {
+    fn get(&self, path: &str) -> Option<Asset> {
+        if self.is_request_allowed(path) {
+            self.source.get(path)
+        } else {
+            None
+        }
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = Cow<'static, str>> + '_> {
+        Box::new(self.source.iter().filter(|x| self.is_request_allowed(x)))
+    }
+
+    fn metadata(&self, path: &str) -> Option<AssetMetadata> {
+        if self.is_request_allowed(path) {
+            self.source.metadata(path)
+        } else {
+            None
+        }
+    }
+
+    /// A compressed file is served only if the uncompressed file is allowed and the sidecar file, such as `app.js.br`, is not excluded.
+    fn get_compressed(&self, path: &str, method: CompressionMethod) -> Option<Asset> {
+        if self.is_allowed(path) && !self.is_excluded(&format!("{}{}", path, method.extension())) {
+            self.source.get_compressed(path, method)
+        } else {
+            None
+        }
+    }
+}
+
+/// Computes a version of the files of `source` from their paths and SHA256 hashes.
+///
+/// The version is the same for the same files, so it does not change on a restart and is the same on every replica.
+pub(crate) fn content_version(source: &dyn AssetSource) -> String {
+    let mut paths: Vec<Cow<'static, str>> = source.iter().collect();
+    paths.sort();
+    let mut hasher = Sha256::new();
+    for path in paths {
+        if let Some(metadata) = source.metadata(&path) {
+            hasher.update(path.as_bytes());
+            hasher.update([0]);
+            hasher.update(metadata.sha256_hash);
+        }
+    }
+    let hash: [u8; 32] = hasher.finalize().into();
+    crate::hash_to_string(&hash)[..16].to_owned()
+}
+
+/// [`MemorySource`] is an [`AssetSource`] that holds files in memory. The files can be added at runtime.
+///
+/// # Example
+/// ```ignore
+/// let mut source = MemorySource::new();
+/// source.insert("index.html", "<h1>Hello</h1>");
+/// let serve_memory = ServeEmbed::from_source(source);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MemorySource {
+    files: HashMap<String, Asset>,
+}
+
+impl MemorySource {
+    /// Creates an empty `MemorySource`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a file at `path`. If a file already exists at `path`, it is replaced.
+    pub fn insert(&mut self, path: impl Into<String>, data: impl Into<Bytes>) -> &mut Self {
+        self.insert_asset(path, Asset::new(data))
+    }
+
+    /// Adds an [`Asset`] at `path`. If a file already exists at `path`, it is replaced.
+    pub fn insert_asset(&mut self, path: impl Into<String>, asset: Asset) -> &mut Self {
+        self.files
+            .insert(path.into().trim_start_matches('/').to_owned(), asset);
+        self
+    }
+
+    /// Removes the file at `path` and returns it.
+    pub fn remove(&mut self, path: &str) -> Option<Asset> {
+        self.files.remove(path.trim_start_matches('/'))
+    }
+}
+
+impl<K: Into<String>, V: Into<Bytes>> FromIterator<(K, V)> for MemorySource {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut source = Self::new();
+        for (path, data) in iter {
+            source.insert(path, data);
+        }
+        source
+    }
+}
+
+impl AssetSource for MemorySource {
+    fn get(&self, path: &str) -> Option<Asset> {
+        self.files.get(path).cloned()
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = Cow<'static, str>> + '_> {
+        Box::new(self.files.keys().map(|x| Cow::Owned(x.clone())))
+    }
+
+    fn metadata(&self, path: &str) -> Option<AssetMetadata> {
+        self.files.get(path).map(|x| x.metadata)
+    }
+}
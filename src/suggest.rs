@@ -0,0 +1,124 @@
+use crate::{AssetSource, CompressionMethod};
+
+/// The maximum number of suggestions for a path.
+const MAX_SUGGESTIONS: usize = 3;
+
+/// Paths longer than this are not compared, to bound the cost of a request.
+const MAX_PATH_LENGTH: usize = 256;
+
+/// A path that is close to a requested path that was not found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Suggestion {
+    /// The normalized path of the file, relative to the root.
+    pub(crate) path: String,
+    /// `true` if the path differs only in case, or in a file extension of the same MIME type, such as `htm` and `html`.
+    pub(crate) confident: bool,
+}
+
+/// An index of the paths of a source, built once and searched by edit distance.
+#[derive(Debug)]
+pub(crate) struct PathIndex {
+    paths: Vec<(String, String)>,
+}
+
+impl PathIndex {
+    /// Builds an index of the files of `source`. Compressed sidecar files are not included.
+    pub(crate) fn new(source: &dyn AssetSource) -> Self {
+        let mut paths: Vec<(String, String)> = source
+            .iter()
+            .filter(|x| {
+                ![
+                    CompressionMethod::Brotli,
+                    CompressionMethod::Gzip,
+                    CompressionMethod::Zlib,
+                ]
+                .iter()
+                .any(|method| x.ends_with(method.extension()))
+            })
+            .map(|x| (x.to_lowercase(), x.into_owned()))
+            .collect();
+        paths.sort();
+        Self { paths }
+    }
+
+    /// Returns up to three paths that are close to `path`, a normalized path relative to the root, closest first.
+    ///
+    /// Paths are compared case-insensitively. The allowed edit distance grows with the length of `path`, up to 3.
+    pub(crate) fn suggest(&self, path: &str) -> Vec<Suggestion> {
+        if path.is_empty() || path.len() > MAX_PATH_LENGTH {
+            return Vec::new();
+        }
+        let path = path.to_lowercase();
+        let path_chars: Vec<char> = path.chars().collect();
+        let max_distance = (path_chars.len() / 4).clamp(1, 3);
+
+        let mut candidates: Vec<(usize, Suggestion)> = self
+            .paths
+            .iter()
+            .filter_map(|(lowercase, original)| {
+                let same_stem = stem(lowercase).is_some_and(|x| Some(x) == stem(&path));
+                let distance = if *lowercase == path {
+                    0
+                } else if same_stem {
+                    1
+                } else if lowercase.chars().count().abs_diff(path_chars.len()) > max_distance {
+                    return None;
+                } else {
+                    edit_distance(&path_chars, lowercase)
+                };
+                (distance <= max_distance).then(|| {
+                    (
+                        distance,
+                        Suggestion {
+                            path: original.clone(),
+                            confident: distance == 0 || (same_stem && same_mime(&path, lowercase)),
+                        },
+                    )
+                })
+            })
+            .collect();
+        candidates.sort_by_key(|(distance, _)| *distance);
+        // a path that differs only in case is the only one with high confidence
+        if candidates
+            .first()
+            .is_some_and(|(distance, _)| *distance == 0)
+        {
+            for (_, suggestion) in candidates.iter_mut().skip(1) {
+                suggestion.confident = false;
+            }
+        }
+        candidates
+            .into_iter()
+            .take(MAX_SUGGESTIONS)
+            .map(|(_, x)| x)
+            .collect()
+    }
+}
+
+/// Returns the path without the file extension, or `None` if the file name has no extension.
+fn stem(path: &str) -> Option<&str> {
+    let (stem, _) = path.rsplit_once('.')?;
+    (!stem.is_empty() && !stem.ends_with('/') && !path[stem.len()..].contains('/')).then_some(stem)
+}
+
+/// Returns `true` if both paths have a known MIME type and the MIME types are the same.
+fn same_mime(a: &str, b: &str) -> bool {
+    mime_guess::from_path(a)
+        .first_raw()
+        .is_some_and(|x| Some(x) == mime_guess::from_path(b).first_raw())
+}
+
+/// Computes the Levenshtein distance between two strings.
+fn edit_distance(a: &[char], b: &str) -> usize {
+    let mut previous: Vec<usize> = (0..=a.len()).collect();
+    let mut current = vec![0; a.len() + 1];
+    for (i, b_char) in b.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, a_char) in a.iter().enumerate() {
+            let substitution = previous[j] + usize::from(*a_char != b_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[a.len()]
+}
@@ -20,7 +20,7 @@ struct Expected {
 }
 
 impl Expected {
-    async fn test(&self, assets: ServeEmbed<Assets>) -> anyhow::Result<()> {
+    async fn test<E: AssetSource>(&self, assets: ServeEmbed<E>) -> anyhow::Result<()> {
         let app = axum::Router::new().fallback_service(assets);
         let response = app
             .oneshot(
@@ -126,7 +126,7 @@ async fn test_default_index() -> anyhow::Result<()> {
         status: http::StatusCode::MOVED_PERMANENTLY,
         content_type: "text/plain",
         encoding: None,
-        location: Some("/subdir/"),
+        location: Some("subdir/"),
         body: b"Moved permanently",
     }
     .test(assets.clone())
@@ -367,7 +367,7 @@ async fn test_fallback_ok() -> anyhow::Result<()> {
         status: http::StatusCode::MOVED_PERMANENTLY,
         content_type: "text/plain",
         encoding: None,
-        location: Some("/subdir/"),
+        location: Some("subdir/"),
         body: b"Moved permanently",
     }
     .test(assets.clone())
@@ -488,7 +488,7 @@ async fn test_redirect() -> anyhow::Result<()> {
         status: http::StatusCode::MOVED_PERMANENTLY,
         content_type: "text/plain",
         encoding: None,
-        location: Some("/subdir/"),
+        location: Some("subdir/"),
         body: b"Moved permanently",
     }
     .test(assets.clone())
@@ -499,7 +499,7 @@ async fn test_redirect() -> anyhow::Result<()> {
         status: http::StatusCode::TEMPORARY_REDIRECT,
         content_type: "text/plain",
         encoding: None,
-        location: Some("/404.html"),
+        location: Some("../404.html"),
         body: b"Temporary redirect",
     }
     .test(assets.clone())
@@ -521,7 +521,7 @@ async fn test_redirect() -> anyhow::Result<()> {
         status: http::StatusCode::TEMPORARY_REDIRECT,
         content_type: "text/plain",
         encoding: None,
-        location: Some("/404.html"),
+        location: Some("../../404.html"),
         body: b"Temporary redirect",
     }
     .test(assets.clone())
@@ -532,7 +532,7 @@ async fn test_redirect() -> anyhow::Result<()> {
         status: http::StatusCode::TEMPORARY_REDIRECT,
         content_type: "text/plain",
         encoding: None,
-        location: Some("/404.html"),
+        location: Some("404.html"),
         body: b"Temporary redirect",
     }
     .test(assets.clone())
@@ -609,7 +609,7 @@ async fn test_custom_404() -> anyhow::Result<()> {
         status: http::StatusCode::MOVED_PERMANENTLY,
         content_type: "text/plain",
         encoding: None,
-        location: Some("/subdir/"),
+        location: Some("subdir/"),
         body: b"Moved permanently",
     }
     .test(assets.clone())
@@ -661,3 +661,2495 @@ async fn test_custom_404() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn test_builder() -> anyhow::Result<()> {
+    let assets = ServeEmbed::<Assets>::builder()
+        .fallback_file("404.html")
+        .fallback_behavior(FallbackBehavior::Ok)
+        .no_index_file()
+        .build();
+
+    Expected {
+        uri: "/",
+        status: http::StatusCode::OK,
+        content_type: "text/html",
+        encoding: None,
+        location: None,
+        body: include_bytes!("../examples/assets/404.html"),
+    }
+    .test(assets.clone())
+    .await?;
+
+    Expected {
+        uri: "/not-found",
+        status: http::StatusCode::OK,
+        content_type: "text/html",
+        encoding: None,
+        location: None,
+        body: include_bytes!("../examples/assets/404.html"),
+    }
+    .test(assets.clone())
+    .await?;
+
+    let assets = ServeEmbed::<Assets>::builder()
+        .index_file("index.html")
+        .fallback_file("404.html")
+        .fallback_behavior(FallbackBehavior::Redirect)
+        .build();
+
+    Expected {
+        uri: "/subdir/",
+        status: http::StatusCode::OK,
+        content_type: "text/html",
+        encoding: Some("br"),
+        location: None,
+        body: include_bytes!("../examples/assets/subdir/index.html.br"),
+    }
+    .test(assets.clone())
+    .await?;
+
+    Expected {
+        uri: "/not-found",
+        status: http::StatusCode::TEMPORARY_REDIRECT,
+        content_type: "text/plain",
+        encoding: None,
+        location: Some("404.html"),
+        body: b"Temporary redirect",
+    }
+    .test(assets.clone())
+    .await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_memory_source() -> anyhow::Result<()> {
+    let mut source = MemorySource::new();
+    source
+        .insert("index.html", "<h1>memory</h1>")
+        .insert("app.js", "console.log('plain');")
+        .insert("app.js.gz", "compressed")
+        .insert("docs/index.html", "<h1>docs</h1>");
+    let assets = ServeEmbed::builder_from_source(source)
+        .fallback_file("index.html")
+        .fallback_behavior(FallbackBehavior::Ok)
+        .build();
+
+    Expected {
+        uri: "/",
+        status: http::StatusCode::OK,
+        content_type: "text/html",
+        encoding: None,
+        location: None,
+        body: b"<h1>memory</h1>",
+    }
+    .test(assets.clone())
+    .await?;
+
+    Expected {
+        uri: "/app.js",
+        status: http::StatusCode::OK,
+        content_type: "application/javascript",
+        encoding: Some("gzip"),
+        location: None,
+        body: b"compressed",
+    }
+    .test(assets.clone())
+    .await?;
+
+    Expected {
+        uri: "/docs",
+        status: http::StatusCode::MOVED_PERMANENTLY,
+        content_type: "text/plain",
+        encoding: None,
+        location: Some("docs/"),
+        body: b"Moved permanently",
+    }
+    .test(assets.clone())
+    .await?;
+
+    Expected {
+        uri: "/missing",
+        status: http::StatusCode::OK,
+        content_type: "text/html",
+        encoding: None,
+        location: None,
+        body: b"<h1>memory</h1>",
+    }
+    .test(assets.clone())
+    .await?;
+
+    Ok(())
+}
+
+#[test]
+fn test_rust_embed_source() {
+    let file = AssetSource::get(&Assets, "index.html").unwrap();
+    let embedded = Assets::get("index.html").unwrap();
+    assert_eq!(&file.data[..], &embedded.data[..]);
+    assert_eq!(file.metadata.sha256_hash, embedded.metadata.sha256_hash());
+    assert_eq!(file.metadata.size, embedded.data.len() as u64);
+    assert_eq!(
+        AssetSource::metadata(&Assets, "index.html"),
+        Some(file.metadata)
+    );
+    assert!(AssetSource::iter(&Assets).any(|x| x == "subdir/index.html"));
+    assert!(AssetSource::get(&Assets, "not-found").is_none());
+}
+
+fn temp_folder(name: &str) -> anyhow::Result<std::path::PathBuf> {
+    let folder = std::env::temp_dir().join(format!("axum-embed-{}-{}", name, std::process::id()));
+    if folder.exists() {
+        std::fs::remove_dir_all(&folder)?;
+    }
+    std::fs::create_dir_all(folder.join("sub"))?;
+    Ok(folder)
+}
+
+#[test]
+fn test_dir_source() -> anyhow::Result<()> {
+    let folder = temp_folder("dir-source")?;
+    std::fs::write(folder.join("index.html"), "<h1>index</h1>")?;
+    std::fs::write(folder.join("sub/page.txt"), "page")?;
+    std::fs::write(folder.join("sub/../outside.txt"), "outside")?;
+
+    let source = DirSource::new(folder.join("sub"));
+    assert_eq!(&source.get("page.txt").unwrap().data[..], b"page");
+    assert_eq!(
+        source.get("page.txt").unwrap().metadata.sha256_hash,
+        Asset::new("page").metadata.sha256_hash
+    );
+    assert!(source
+        .get("page.txt")
+        .unwrap()
+        .metadata
+        .last_modified
+        .is_some());
+    assert!(source.get("../outside.txt").is_none());
+    assert!(source.get("./page.txt").is_none());
+    assert!(source.get("/page.txt").is_none());
+    assert!(source.get("..\\outside.txt").is_none());
+    assert!(source.get("").is_none());
+
+    let mut paths: Vec<_> = DirSource::new(&folder).iter().collect();
+    paths.sort();
+    assert_eq!(paths, vec!["index.html", "outside.txt", "sub/page.txt"]);
+
+    #[cfg(unix)]
+    {
+        std::os::unix::fs::symlink(folder.join("outside.txt"), folder.join("sub/link.txt"))?;
+        assert!(source.get("link.txt").is_none());
+    }
+
+    std::fs::remove_dir_all(&folder)?;
+    Ok(())
+}
+
+#[cfg(feature = "dev")]
+#[tokio::test]
+async fn test_live_reload() -> anyhow::Result<()> {
+    let folder = temp_folder("live-reload")?;
+    std::fs::write(
+        folder.join("index.html"),
+        "<html><body><h1>index</h1></body></html>",
+    )?;
+    std::fs::write(folder.join("index.html.br"), "compressed")?;
+    std::fs::write(folder.join("sub/page.html"), "<h1>page</h1>")?;
+
+    let assets = ServeEmbed::<Assets>::builder()
+        .dev_folder(&folder)
+        .live_reload(true)
+        .build();
+
+    Expected {
+        uri: "/",
+        status: http::StatusCode::OK,
+        content_type: "text/html",
+        encoding: None,
+        location: None,
+        body: b"<html><body><h1>index</h1><script>new EventSource(\"__axum_embed/live-reload\").onmessage=function(){location.reload()};</script></body></html>",
+    }
+    .test(assets.clone())
+    .await?;
+
+    Expected {
+        uri: "/sub/page.html",
+        status: http::StatusCode::OK,
+        content_type: "text/html",
+        encoding: None,
+        location: None,
+        body: b"<h1>page</h1><script>new EventSource(\"../__axum_embed/live-reload\").onmessage=function(){location.reload()};</script>",
+    }
+    .test(assets.clone())
+    .await?;
+
+    let app = axum::Router::new().fallback_service(assets);
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/__axum_embed/live-reload")
+                .body(Body::empty())?,
+        )
+        .await?;
+    assert_eq!(
+        response.headers().get(http::header::CONTENT_TYPE).unwrap(),
+        "text/event-stream"
+    );
+    let mut body = response.into_body();
+    let frame = body.frame().await.unwrap()?;
+    assert_eq!(frame.into_data().unwrap(), "retry: 1000\n\n");
+
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    std::fs::write(folder.join("sub/new.css"), "body {}")?;
+    let frame = tokio::time::timeout(std::time::Duration::from_secs(5), body.frame())
+        .await?
+        .unwrap()?;
+    assert_eq!(frame.into_data().unwrap(), "data: reload\n\n");
+
+    std::fs::remove_dir_all(&folder)?;
+    Ok(())
+}
+
+#[cfg(feature = "zip")]
+fn zip_archive() -> anyhow::Result<Vec<u8>> {
+    use std::io::Write;
+
+    let deflated = ::zip::write::SimpleFileOptions::default()
+        .compression_method(::zip::CompressionMethod::Deflated);
+    let stored = ::zip::write::SimpleFileOptions::default()
+        .compression_method(::zip::CompressionMethod::Stored);
+    let mut writer = ::zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    writer.start_file("index.html", deflated)?;
+    writer.write_all(include_bytes!("../examples/assets/index.html"))?;
+    writer.start_file("app.js", stored)?;
+    writer.write_all(b"console.log('zip');")?;
+    writer.start_file("app.js.br", stored)?;
+    writer.write_all(b"brotli")?;
+    writer.add_directory("sub/", stored)?;
+    writer.start_file("sub/index.html", stored)?;
+    writer.write_all(b"<h1>sub</h1>")?;
+    Ok(writer.finish()?.into_inner())
+}
+
+#[cfg(feature = "zip")]
+#[tokio::test]
+async fn test_zip_source() -> anyhow::Result<()> {
+    use std::io::Read;
+
+    let data = zip_archive()?;
+    let assets = ServeEmbed::from_source(ZipSource::from_static(data.clone().leak())?);
+
+    Expected {
+        uri: "/app.js",
+        status: http::StatusCode::OK,
+        content_type: "application/javascript",
+        encoding: Some("br"),
+        location: None,
+        body: b"brotli",
+    }
+    .test(assets.clone())
+    .await?;
+
+    Expected {
+        uri: "/sub",
+        status: http::StatusCode::MOVED_PERMANENTLY,
+        content_type: "text/plain",
+        encoding: None,
+        location: Some("sub/"),
+        body: b"Moved permanently",
+    }
+    .test(assets.clone())
+    .await?;
+
+    Expected {
+        uri: "/sub/",
+        status: http::StatusCode::OK,
+        content_type: "text/html",
+        encoding: None,
+        location: None,
+        body: b"<h1>sub</h1>",
+    }
+    .test(assets.clone())
+    .await?;
+
+    // deflated entries are served without compressing them again
+    let app = axum::Router::new().fallback_service(assets.clone());
+    for (accept_encoding, content_encoding) in [
+        ("br, gzip", Some("gzip")),
+        ("deflate", Some("deflate")),
+        ("", None),
+    ] {
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/")
+                    .header(http::header::ACCEPT_ENCODING, accept_encoding)
+                    .body(Body::empty())?,
+            )
+            .await?;
+        assert_eq!(response.status(), http::StatusCode::OK);
+        assert_eq!(
+            response
+                .headers()
+                .get(http::header::CONTENT_ENCODING)
+                .map(|x| x.to_str().unwrap()),
+            content_encoding
+        );
+        assert!(response.headers().contains_key(http::header::ETAG));
+        assert!(response.headers().contains_key(http::header::LAST_MODIFIED));
+        let data = response.into_body().collect().await?.to_bytes();
+        let mut decoded = Vec::new();
+        match content_encoding {
+            Some("gzip") => {
+                flate2::read::GzDecoder::new(&data[..]).read_to_end(&mut decoded)?;
+            }
+            Some("deflate") => {
+                flate2::read::ZlibDecoder::new(&data[..]).read_to_end(&mut decoded)?;
+            }
+            _ => decoded.extend_from_slice(&data),
+        }
+        assert_eq!(
+            &decoded[..],
+            include_bytes!("../examples/assets/index.html")
+        );
+    }
+
+    let path = std::env::temp_dir().join(format!("axum-embed-{}.zip", std::process::id()));
+    std::fs::write(&path, &data)?;
+    let source = ZipSource::open(&path)?;
+    std::fs::remove_file(&path)?;
+    let mut paths: Vec<_> = source.iter().collect();
+    paths.sort();
+    assert_eq!(
+        paths,
+        vec!["app.js", "app.js.br", "index.html", "sub/index.html"]
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_overlay_source() -> anyhow::Result<()> {
+    let mut overrides = MemorySource::new();
+    overrides
+        .insert("sample.js", "console.log('override');")
+        .insert("404.html", "<h1>override 404</h1>")
+        .insert("product/index.html", "<h1>product</h1>");
+    let source = OverlaySource::new().layer(overrides).embed::<Assets>();
+    let assets = ServeEmbed::builder_from_source(source)
+        .fallback_file("404.html")
+        .build();
+
+    // the sidecar of the base layer is not used for the overridden file
+    Expected {
+        uri: "/sample.js",
+        status: http::StatusCode::OK,
+        content_type: "application/javascript",
+        encoding: None,
+        location: None,
+        body: b"console.log('override');",
+    }
+    .test(assets.clone())
+    .await?;
+
+    Expected {
+        uri: "/style.css",
+        status: http::StatusCode::OK,
+        content_type: "text/css",
+        encoding: Some("gzip"),
+        location: None,
+        body: include_bytes!("../examples/assets/style.css.gz"),
+    }
+    .test(assets.clone())
+    .await?;
+
+    Expected {
+        uri: "/product",
+        status: http::StatusCode::MOVED_PERMANENTLY,
+        content_type: "text/plain",
+        encoding: None,
+        location: Some("product/"),
+        body: b"Moved permanently",
+    }
+    .test(assets.clone())
+    .await?;
+
+    Expected {
+        uri: "/subdir/",
+        status: http::StatusCode::OK,
+        content_type: "text/html",
+        encoding: Some("br"),
+        location: None,
+        body: include_bytes!("../examples/assets/subdir/index.html.br"),
+    }
+    .test(assets.clone())
+    .await?;
+
+    Expected {
+        uri: "/not-found",
+        status: http::StatusCode::NOT_FOUND,
+        content_type: "text/html",
+        encoding: None,
+        location: None,
+        body: b"<h1>override 404</h1>",
+    }
+    .test(assets.clone())
+    .await?;
+
+    let source = OverlaySource::new()
+        .layer(MemorySource::from_iter([("index.html", "override")]))
+        .embed::<Assets>();
+    let paths: Vec<_> = source.iter().collect();
+    assert_eq!(paths.iter().filter(|x| *x == "index.html").count(), 1);
+    assert_eq!(paths.len(), Assets::iter().count());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_override_dir() -> anyhow::Result<()> {
+    let folder = temp_folder("override-dir")?;
+    std::fs::write(folder.join("sample.js"), "console.log('override');")?;
+    std::fs::write(folder.join("sub/config.json"), "{}")?;
+    std::fs::write(folder.join("secret.txt"), "secret")?;
+    let overrides = folder.join("sub");
+
+    let assets = ServeEmbed::<Assets>::builder()
+        .override_dir(&folder)
+        .build();
+
+    Expected {
+        uri: "/sample.js",
+        status: http::StatusCode::OK,
+        content_type: "application/javascript",
+        encoding: None,
+        location: None,
+        body: b"console.log('override');",
+    }
+    .test(assets.clone())
+    .await?;
+
+    Expected {
+        uri: "/style.css",
+        status: http::StatusCode::OK,
+        content_type: "text/css",
+        encoding: Some("gzip"),
+        location: None,
+        body: include_bytes!("../examples/assets/style.css.gz"),
+    }
+    .test(assets.clone())
+    .await?;
+
+    let app = axum::Router::new().fallback_service(assets);
+    let response = app
+        .clone()
+        .oneshot(Request::builder().uri("/sample.js").body(Body::empty())?)
+        .await?;
+    let etag = response.headers().get(http::header::ETAG).unwrap().clone();
+    assert!(response.headers().contains_key(http::header::LAST_MODIFIED));
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/sample.js")
+                .header(http::header::IF_NONE_MATCH, etag)
+                .body(Body::empty())?,
+        )
+        .await?;
+    assert_eq!(response.status(), http::StatusCode::NOT_MODIFIED);
+
+    // files outside of the override folder cannot be reached
+    let assets = ServeEmbed::<Assets>::builder()
+        .override_dir(&overrides)
+        .build();
+    Expected {
+        uri: "/config.json",
+        status: http::StatusCode::OK,
+        content_type: "application/json",
+        encoding: None,
+        location: None,
+        body: b"{}",
+    }
+    .test(assets.clone())
+    .await?;
+    for uri in ["/../secret.txt", "/%2e%2e/secret.txt", "/..%2fsecret.txt"] {
+        Expected {
+            uri,
+            status: http::StatusCode::NOT_FOUND,
+            content_type: "text/html",
+            encoding: None,
+            location: None,
+            body: include_bytes!("assets/404.html"),
+        }
+        .test(assets.clone())
+        .await?;
+    }
+
+    std::fs::remove_dir_all(&folder)?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_root() -> anyhow::Result<()> {
+    let assets = ServeEmbed::<Assets>::builder()
+        .root("/subdir/")
+        .fallback_file("index.html")
+        .fallback_behavior(FallbackBehavior::Ok)
+        .build();
+
+    Expected {
+        uri: "/",
+        status: http::StatusCode::OK,
+        content_type: "text/html",
+        encoding: Some("br"),
+        location: None,
+        body: include_bytes!("../examples/assets/subdir/index.html.br"),
+    }
+    .test(assets.clone())
+    .await?;
+
+    Expected {
+        uri: "/deep/link",
+        status: http::StatusCode::OK,
+        content_type: "text/html",
+        encoding: Some("br"),
+        location: None,
+        body: include_bytes!("../examples/assets/subdir/index.html.br"),
+    }
+    .test(assets.clone())
+    .await?;
+
+    // files outside of the root are unreachable
+    for uri in [
+        "/sample.js",
+        "/../sample.js",
+        "/%2e%2e/sample.js",
+        "/a/../../sample.js",
+    ] {
+        Expected {
+            uri,
+            status: http::StatusCode::OK,
+            content_type: "text/html",
+            encoding: Some("br"),
+            location: None,
+            body: include_bytes!("../examples/assets/subdir/index.html.br"),
+        }
+        .test(assets.clone())
+        .await?;
+    }
+
+    // redirects stay under the mount point
+    let mut source = MemorySource::new();
+    source
+        .insert("admin/login.html", "login")
+        .insert("admin/sub/index.html", "sub");
+    let app = axum::Router::new().nest_service(
+        "/admin",
+        ServeEmbed::builder_from_source(source)
+            .root("admin")
+            .fallback_file("login.html")
+            .fallback_behavior(FallbackBehavior::Redirect)
+            .build(),
+    );
+    for (uri, location) in [
+        ("/admin/sub", "sub/"),
+        ("/admin/sub/missing", "../login.html"),
+    ] {
+        let response = app
+            .clone()
+            .oneshot(Request::builder().uri(uri).body(Body::empty())?)
+            .await?;
+        assert!(response.status().is_redirection(), "{}", uri);
+        assert_eq!(
+            response.headers()[http::header::LOCATION],
+            location,
+            "{}",
+            uri
+        );
+    }
+
+    let assets = ServeEmbed::<Assets>::builder().root("images").build();
+    Expected {
+        uri: "/fox",
+        status: http::StatusCode::NOT_FOUND,
+        content_type: "text/html",
+        encoding: None,
+        location: None,
+        body: include_bytes!("assets/404.html"),
+    }
+    .test(assets.clone())
+    .await?;
+    Expected {
+        uri: "/fox/fox.webp",
+        status: http::StatusCode::OK,
+        content_type: "image/webp",
+        encoding: None,
+        location: None,
+        body: include_bytes!("../examples/assets/images/fox/fox.webp"),
+    }
+    .test(assets.clone())
+    .await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_path_normalization() -> anyhow::Result<()> {
+    let assets = ServeEmbed::<Assets>::new();
+
+    for uri in [
+        "/subdir/../sample.js",
+        "/./sample.js",
+        "//sample.js",
+        "/%73ample.js",
+    ] {
+        Expected {
+            uri,
+            status: http::StatusCode::OK,
+            content_type: "application/javascript",
+            encoding: Some("br"),
+            location: None,
+            body: include_bytes!("../examples/assets/sample.js.br"),
+        }
+        .test(assets.clone())
+        .await?;
+    }
+
+    for uri in ["/../sample.js", "/subdir%2f..%2fsample.js", "/%ff", "/%zz"] {
+        Expected {
+            uri,
+            status: http::StatusCode::NOT_FOUND,
+            content_type: "text/html",
+            encoding: None,
+            location: None,
+            body: include_bytes!("assets/404.html"),
+        }
+        .test(assets.clone())
+        .await?;
+    }
+
+    Expected {
+        uri: "/images/../subdir",
+        status: http::StatusCode::MOVED_PERMANENTLY,
+        content_type: "text/plain",
+        encoding: None,
+        location: Some("../../subdir/"),
+        body: b"Moved permanently",
+    }
+    .test(assets.clone())
+    .await?;
+
+    let mut source = MemorySource::new();
+    source.insert("my docs/index.html", "docs");
+    Expected {
+        uri: "/my%20docs",
+        status: http::StatusCode::MOVED_PERMANENTLY,
+        content_type: "text/plain",
+        encoding: None,
+        location: Some("my%20docs/"),
+        body: b"Moved permanently",
+    }
+    .test(ServeEmbed::from_source(source))
+    .await?;
+
+    Ok(())
+}
+
+#[test]
+fn test_glob() {
+    let glob = Glob::new("/templates/**");
+    assert!(glob.is_match("templates/index.html"));
+    assert!(glob.is_match("templates/mail/welcome.txt"));
+    assert!(!glob.is_match("public/templates/index.html"));
+
+    let glob = Glob::new("*.html");
+    assert!(glob.is_match("index.html"));
+    assert!(!glob.is_match("subdir/index.html"));
+
+    let glob = Glob::new("**/*.html");
+    assert!(glob.is_match("index.html"));
+    assert!(glob.is_match("subdir/index.html"));
+    assert!(!glob.is_match("index.htm"));
+
+    let glob = Glob::new("images/fox?.webp");
+    assert!(glob.is_match("images/fox2.webp"));
+    assert!(!glob.is_match("images/fox.webp"));
+    assert!(!glob.is_match("images/fox/.webp"));
+}
+
+#[tokio::test]
+async fn test_include_exclude() -> anyhow::Result<()> {
+    let assets = ServeEmbed::<Assets>::builder()
+        .exclude("subdir/**")
+        .exclude("*.css")
+        .build();
+
+    for uri in [
+        "/subdir",
+        "/subdir/",
+        "/subdir/index.html",
+        "/style.css",
+        "/style.css.gz",
+    ] {
+        Expected {
+            uri,
+            status: http::StatusCode::NOT_FOUND,
+            content_type: "text/html",
+            encoding: None,
+            location: None,
+            body: include_bytes!("assets/404.html"),
+        }
+        .test(assets.clone())
+        .await?;
+    }
+
+    Expected {
+        uri: "/sample.js",
+        status: http::StatusCode::OK,
+        content_type: "application/javascript",
+        encoding: Some("br"),
+        location: None,
+        body: include_bytes!("../examples/assets/sample.js.br"),
+    }
+    .test(assets.clone())
+    .await?;
+
+    let assets = ServeEmbed::<Assets>::builder()
+        .include("index.html")
+        .include("*.js")
+        .exclude("*.br")
+        .build();
+
+    Expected {
+        uri: "/",
+        status: http::StatusCode::OK,
+        content_type: "text/html",
+        encoding: None,
+        location: None,
+        body: include_bytes!("../examples/assets/index.html"),
+    }
+    .test(assets.clone())
+    .await?;
+
+    Expected {
+        uri: "/sample.js",
+        status: http::StatusCode::OK,
+        content_type: "application/javascript",
+        encoding: None,
+        location: None,
+        body: include_bytes!("../examples/assets/sample.js"),
+    }
+    .test(assets.clone())
+    .await?;
+
+    Expected {
+        uri: "/images/fox/fox.webp",
+        status: http::StatusCode::NOT_FOUND,
+        content_type: "text/html",
+        encoding: None,
+        location: None,
+        body: include_bytes!("assets/404.html"),
+    }
+    .test(assets.clone())
+    .await?;
+
+    // a file with a compression extension is matched by its own name
+    let mut source = MemorySource::new();
+    source
+        .insert("downloads/archive.tar.gz", "archive")
+        .insert("downloads/notes.tar", "notes");
+    let assets = ServeEmbed::builder_from_source(source.clone())
+        .include("downloads/*.gz")
+        .build();
+    for (uri, status) in [
+        ("/downloads/archive.tar.gz", http::StatusCode::OK),
+        ("/downloads/notes.tar", http::StatusCode::NOT_FOUND),
+    ] {
+        let response = assets
+            .clone()
+            .oneshot(Request::builder().uri(uri).body(Body::empty())?)
+            .await?;
+        assert_eq!(response.status(), status, "{}", uri);
+    }
+    let assets = ServeEmbed::builder_from_source(source)
+        .exclude("**/*.tar")
+        .build();
+    for (uri, status) in [
+        ("/downloads/archive.tar.gz", http::StatusCode::OK),
+        ("/downloads/notes.tar", http::StatusCode::NOT_FOUND),
+    ] {
+        let response = assets
+            .clone()
+            .oneshot(Request::builder().uri(uri).body(Body::empty())?)
+            .await?;
+        assert_eq!(response.status(), status, "{}", uri);
+    }
+
+    // a sidecar file of an excluded file is not served
+    let mut source = MemorySource::new();
+    source
+        .insert("emails/welcome.tera", "SECRET")
+        .insert("emails/welcome.tera.gz", "gz-of-SECRET");
+    let assets = ServeEmbed::builder_from_source(source)
+        .exclude("**/*.tera")
+        .build();
+    for uri in ["/emails/welcome.tera", "/emails/welcome.tera.gz"] {
+        let response = assets
+            .clone()
+            .oneshot(Request::builder().uri(uri).body(Body::empty())?)
+            .await?;
+        assert_eq!(response.status(), http::StatusCode::NOT_FOUND, "{}", uri);
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_virtual_hosts() -> anyhow::Result<()> {
+    let mut docs = MemorySource::new();
+    docs.insert("index.html", "docs");
+    let mut app = MemorySource::new();
+    app.insert("index.html", "app");
+    let mut tenant = MemorySource::new();
+    tenant.insert("index.html", "tenant");
+
+    let hosts = VirtualHosts::new()
+        .host("docs.example.com", ServeEmbed::from_source(docs))
+        .host(
+            "*.example.com",
+            ServeEmbed::builder_from_source(app)
+                .fallback_file("index.html")
+                .fallback_behavior(FallbackBehavior::Ok)
+                .build(),
+        )
+        .host("*.tenant.example.com", ServeEmbed::from_source(tenant))
+        .default_host(ServeEmbed::<Assets>::new());
+
+    for (host, uri, status, body) in [
+        ("docs.example.com", "/", StatusCode::OK, &b"docs"[..]),
+        ("DOCS.example.com.:8080", "/", StatusCode::OK, b"docs"),
+        (
+            "docs.example.com",
+            "/missing",
+            StatusCode::NOT_FOUND,
+            include_bytes!("assets/404.html"),
+        ),
+        ("app.example.com", "/", StatusCode::OK, b"app"),
+        ("app.example.com", "/missing", StatusCode::OK, b"app"),
+        ("a.b.example.com", "/", StatusCode::OK, b"app"),
+        ("acme.tenant.example.com", "/", StatusCode::OK, b"tenant"),
+        (
+            "example.com",
+            "/",
+            StatusCode::OK,
+            include_bytes!("../examples/assets/index.html"),
+        ),
+        (
+            "[::1]:8080",
+            "/",
+            StatusCode::OK,
+            include_bytes!("../examples/assets/index.html"),
+        ),
+    ] {
+        let response = hosts
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri(uri)
+                    .header(http::header::HOST, host)
+                    .body(Body::empty())?,
+            )
+            .await?;
+        assert_eq!(response.status(), status, "{}{}", host, uri);
+        let data = response.into_body().collect().await?.to_bytes();
+        assert_eq!(&data[..], body, "{}{}", host, uri);
+    }
+
+    // the authority of the request URI is used without the `Host` header
+    let response = hosts
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("https://docs.example.com/")
+                .body(Body::empty())?,
+        )
+        .await?;
+    let data = response.into_body().collect().await?.to_bytes();
+    assert_eq!(&data[..], b"docs");
+
+    // the default 404 page is served without a default host
+    let response = VirtualHosts::new()
+        .oneshot(
+            Request::builder()
+                .uri("/")
+                .header(http::header::HOST, "docs.example.com")
+                .body(Body::empty())?,
+        )
+        .await?;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_bundle_swap() -> anyhow::Result<()> {
+    let mut source = MemorySource::new();
+    source.insert("index.html", "version 1");
+    let assets = ServeEmbed::builder_from_source(source)
+        .bundle_version("1")
+        .build();
+    let handle = assets.handle();
+    assert_eq!(handle.bundle_version(), "1");
+
+    // a request that has started before the swap is served from the old bundle
+    let in_flight = assets
+        .clone()
+        .call(Request::builder().uri("/").body(Body::empty())?);
+
+    handle.swap(
+        ServeEmbed::<Assets>::builder()
+            .fallback_file("index.html")
+            .fallback_behavior(FallbackBehavior::Ok)
+            .bundle_version("2")
+            .build(),
+    );
+    assert_eq!(handle.bundle_version(), "2");
+
+    let response = in_flight.await?;
+    assert_eq!(response.headers().get("x-bundle-version").unwrap(), "1");
+    let data = response.into_body().collect().await?.to_bytes();
+    assert_eq!(&data[..], b"version 1");
+
+    for uri in ["/", "/missing"] {
+        let response = assets
+            .clone()
+            .oneshot(Request::builder().uri(uri).body(Body::empty())?)
+            .await?;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers().get("x-bundle-version").unwrap(), "2");
+        let data = response.into_body().collect().await?.to_bytes();
+        assert_eq!(&data[..], include_bytes!("../examples/assets/index.html"));
+    }
+
+    // a swap is reflected in virtual hosts
+    let hosts = VirtualHosts::new().default_host(assets.clone());
+    handle.swap(ServeEmbed::from_source(MemorySource::new()));
+    let response = hosts
+        .oneshot(Request::builder().uri("/").body(Body::empty())?)
+        .await?;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert_eq!(
+        response.headers()["x-bundle-version"],
+        handle.bundle_version().as_str()
+    );
+
+    // without a version, the version is computed from the files
+    let bundle = |content: &'static str| {
+        let mut source = MemorySource::new();
+        source.insert("index.html", content);
+        ServeEmbed::builder_from_source(source).build()
+    };
+    let version = |assets: ServeEmbed<MemorySource>| async move {
+        let response = assets
+            .oneshot(Request::builder().uri("/").body(Body::empty())?)
+            .await?;
+        anyhow::Ok(response.headers()["x-bundle-version"].clone())
+    };
+    let assets = bundle("version 1");
+    let handle = assets.handle();
+    let version1 = version(assets.clone()).await?;
+    assert_eq!(version1, handle.bundle_version().as_str());
+    assert_eq!(version(bundle("version 1")).await?, version1);
+    handle.swap(bundle("version 2"));
+    let version2 = version(assets.clone()).await?;
+    assert_ne!(version1, version2);
+    handle.swap(bundle("version 1"));
+    assert_eq!(version(assets).await?, version1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_spa_mode() -> anyhow::Result<()> {
+    let assets = ServeEmbed::<Assets>::builder()
+        .fallback_file("index.html")
+        .fallback_behavior(FallbackBehavior::Ok)
+        .spa_mode(true)
+        .build();
+
+    for (uri, accept, fetch_mode, status, body) in [
+        (
+            "/app/settings",
+            None,
+            None,
+            StatusCode::OK,
+            &include_bytes!("../examples/assets/index.html")[..],
+        ),
+        (
+            "/assets/app.1234.js",
+            None,
+            None,
+            StatusCode::NOT_FOUND,
+            include_bytes!("assets/404.html"),
+        ),
+        (
+            "/assets/app.1234.js",
+            Some("*/*"),
+            Some("no-cors"),
+            StatusCode::NOT_FOUND,
+            include_bytes!("assets/404.html"),
+        ),
+        (
+            "/users/john.doe",
+            Some("text/html,application/xhtml+xml;q=0.9"),
+            None,
+            StatusCode::OK,
+            include_bytes!("../examples/assets/index.html"),
+        ),
+        (
+            "/users/john.doe",
+            None,
+            Some("navigate"),
+            StatusCode::OK,
+            include_bytes!("../examples/assets/index.html"),
+        ),
+        (
+            "/sample.js",
+            Some("*/*"),
+            None,
+            StatusCode::OK,
+            include_bytes!("../examples/assets/sample.js"),
+        ),
+    ] {
+        let mut request = Request::builder().uri(uri);
+        if let Some(accept) = accept {
+            request = request.header(http::header::ACCEPT, accept);
+        }
+        if let Some(fetch_mode) = fetch_mode {
+            request = request.header("sec-fetch-mode", fetch_mode);
+        }
+        let response = assets.clone().oneshot(request.body(Body::empty())?).await?;
+        assert_eq!(response.status(), status, "{}", uri);
+        // both the fallback file and 404 depend on the request headers
+        assert_eq!(
+            response
+                .headers()
+                .get(http::header::VARY)
+                .map(|x| x.to_str().unwrap()),
+            (uri != "/sample.js").then_some("Accept, Sec-Fetch-Mode"),
+            "{}",
+            uri
+        );
+        let data = response.into_body().collect().await?.to_bytes();
+        assert_eq!(&data[..], body, "{}", uri);
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_fallback_rules() -> anyhow::Result<()> {
+    let source: MemorySource = [
+        ("app/index.html", "app"),
+        ("docs/404.html", "docs not found"),
+        ("admin/login.html", "login"),
+        ("404.html", "not found"),
+    ]
+    .into_iter()
+    .collect();
+    let assets = ServeEmbed::builder_from_source(source)
+        .fallback_file("404.html")
+        .fallback_rule(FallbackRule::prefix(
+            "/app/",
+            "app/index.html",
+            FallbackBehavior::Ok,
+        ))
+        .fallback_rule(FallbackRule::prefix(
+            "docs",
+            "docs/404.html",
+            FallbackBehavior::NotFound,
+        ))
+        .fallback_rule(FallbackRule::glob(
+            "admin/**",
+            "admin/login.html",
+            FallbackBehavior::Redirect,
+        ))
+        .build();
+
+    for (uri, status, content_type, location, body) in [
+        (
+            "/app/settings/profile",
+            StatusCode::OK,
+            "text/html",
+            None,
+            &b"app"[..],
+        ),
+        (
+            "/app",
+            StatusCode::MOVED_PERMANENTLY,
+            "text/plain",
+            Some("app/"),
+            b"Moved permanently",
+        ),
+        ("/app/missing.js", StatusCode::OK, "text/html", None, b"app"),
+        (
+            "/application",
+            StatusCode::NOT_FOUND,
+            "text/html",
+            None,
+            b"not found",
+        ),
+        (
+            "/docs/missing",
+            StatusCode::NOT_FOUND,
+            "text/html",
+            None,
+            b"docs not found",
+        ),
+        (
+            "/admin/users",
+            StatusCode::TEMPORARY_REDIRECT,
+            "text/plain",
+            Some("../admin/login.html"),
+            b"Temporary redirect",
+        ),
+        (
+            "/admin/login.html",
+            StatusCode::OK,
+            "text/html",
+            None,
+            b"login",
+        ),
+        (
+            "/missing",
+            StatusCode::NOT_FOUND,
+            "text/html",
+            None,
+            b"not found",
+        ),
+    ] {
+        Expected {
+            uri,
+            status,
+            content_type,
+            encoding: None,
+            location,
+            body,
+        }
+        .test(assets.clone())
+        .await?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_fallback_service() -> anyhow::Result<()> {
+    let api = axum::Router::new()
+        .route("/api/hello", axum::routing::get(|| async { "hello" }))
+        .route(
+            "/api/echo",
+            axum::routing::post(|body: Bytes| async move { body }),
+        );
+    let app = axum::Router::new().fallback_service(
+        ServeEmbed::<Assets>::builder()
+            .fallback_file("404.html")
+            .build()
+            .fallback_service(api.clone()),
+    );
+
+    for (method, uri, status, body) in [
+        (
+            "GET",
+            "/",
+            StatusCode::OK,
+            &include_bytes!("../examples/assets/index.html")[..],
+        ),
+        ("GET", "/api/hello", StatusCode::OK, b"hello"),
+        ("POST", "/api/echo", StatusCode::OK, b"request body"),
+        ("GET", "/api/missing", StatusCode::NOT_FOUND, b""),
+        (
+            "GET",
+            "/subdir",
+            StatusCode::MOVED_PERMANENTLY,
+            b"Moved permanently",
+        ),
+    ] {
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method(method)
+                    .uri(uri)
+                    .body(Body::from("request body"))?,
+            )
+            .await?;
+        assert_eq!(response.status(), status, "{} {}", method, uri);
+        let data = response.into_body().collect().await?.to_bytes();
+        assert_eq!(&data[..], body, "{} {}", method, uri);
+    }
+
+    let app = tower::ServiceBuilder::new()
+        .layer(ServeEmbedLayer::new(ServeEmbed::<Assets>::new()))
+        .service(api);
+    let response = app
+        .clone()
+        .oneshot(Request::builder().uri("/sample.js").body(Body::empty())?)
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = app
+        .oneshot(Request::builder().uri("/api/hello").body(Body::empty())?)
+        .await?;
+    let data = response
+        .into_body()
+        .collect()
+        .await
+        .map_err(|x| anyhow::anyhow!(x))?
+        .to_bytes();
+    assert_eq!(&data[..], b"hello");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_fallback_status() -> anyhow::Result<()> {
+    let source: MemorySource = [
+        ("maintenance.html", "maintenance"),
+        ("gone.html", "gone"),
+        ("blocked.html", "blocked"),
+    ]
+    .into_iter()
+    .collect();
+    let assets = ServeEmbed::builder_from_source(source)
+        .fallback_file("maintenance.html")
+        .fallback_behavior(FallbackBehavior::Status(StatusCode::SERVICE_UNAVAILABLE))
+        .fallback_header(
+            http::header::RETRY_AFTER,
+            http::HeaderValue::from_static("120"),
+        )
+        .fallback_rule(FallbackRule::prefix(
+            "legacy",
+            "gone.html",
+            FallbackBehavior::Status(StatusCode::GONE),
+        ))
+        .fallback_rule(
+            FallbackRule::glob(
+                "videos/**",
+                "blocked.html",
+                FallbackBehavior::Status(StatusCode::UNAVAILABLE_FOR_LEGAL_REASONS),
+            )
+            .header(
+                http::header::CACHE_CONTROL,
+                http::HeaderValue::from_static("no-store"),
+            ),
+        )
+        .build();
+
+    for (uri, status, retry_after, cache_control, body) in [
+        (
+            "/shop",
+            StatusCode::SERVICE_UNAVAILABLE,
+            Some("120"),
+            None,
+            &b"maintenance"[..],
+        ),
+        ("/legacy/page", StatusCode::GONE, None, None, b"gone"),
+        (
+            "/videos/1",
+            StatusCode::UNAVAILABLE_FOR_LEGAL_REASONS,
+            None,
+            Some("no-store"),
+            b"blocked",
+        ),
+        ("/gone.html", StatusCode::OK, None, None, b"gone"),
+    ] {
+        let response = assets
+            .clone()
+            .oneshot(Request::builder().uri(uri).body(Body::empty())?)
+            .await?;
+        assert_eq!(response.status(), status, "{}", uri);
+        assert_eq!(
+            response
+                .headers()
+                .get(http::header::RETRY_AFTER)
+                .map(|x| x.to_str().unwrap()),
+            retry_after,
+            "{}",
+            uri
+        );
+        assert_eq!(
+            response
+                .headers()
+                .get(http::header::CACHE_CONTROL)
+                .map(|x| x.to_str().unwrap()),
+            cache_control,
+            "{}",
+            uri
+        );
+        assert_eq!(
+            response.headers().get(http::header::CONTENT_TYPE).unwrap(),
+            "text/html"
+        );
+        let data = response.into_body().collect().await?.to_bytes();
+        assert_eq!(&data[..], body, "{}", uri);
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_fallback_revalidation() -> anyhow::Result<()> {
+    let mut source = MemorySource::new();
+    source.insert_asset(
+        "index.html",
+        Asset::new("<h1>app</h1>").with_last_modified(1_700_000_000),
+    );
+    source.insert("404.html", "not found");
+    let etag = hash_to_string(&source.get("index.html").unwrap().metadata.sha256_hash);
+    let assets = ServeEmbed::builder_from_source(source)
+        .fallback_file("index.html")
+        .fallback_behavior(FallbackBehavior::Ok)
+        .fallback_rule(FallbackRule::prefix(
+            "docs",
+            "404.html",
+            FallbackBehavior::NotFound,
+        ))
+        .build();
+
+    let request = |uri: &str, header: Option<(http::HeaderName, String)>| {
+        let mut request = Request::builder().uri(uri);
+        if let Some((name, value)) = header {
+            request = request.header(name, value);
+        }
+        request.body(Body::empty()).unwrap()
+    };
+
+    // a fallback response with 200 has an ETag and a Content-Location
+    let response = assets.clone().oneshot(request("/deep/link", None)).await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers().get(http::header::ETAG).unwrap(),
+        etag.as_str()
+    );
+    assert_eq!(
+        response
+            .headers()
+            .get(http::header::CONTENT_LOCATION)
+            .unwrap(),
+        "../index.html"
+    );
+
+    for header in [
+        (http::header::IF_NONE_MATCH, format!("\"{}\"", etag)),
+        (
+            http::header::IF_NONE_MATCH,
+            format!("\"other\", W/\"{}\"", etag),
+        ),
+        (http::header::IF_NONE_MATCH, "*".to_string()),
+        (
+            http::header::IF_MODIFIED_SINCE,
+            "Tue, 14 Nov 2023 22:13:20 GMT".to_string(),
+        ),
+    ] {
+        let response = assets
+            .clone()
+            .oneshot(request("/deep/link", Some(header.clone())))
+            .await?;
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED, "{:?}", header);
+        assert_eq!(
+            response
+                .headers()
+                .get(http::header::CONTENT_LOCATION)
+                .unwrap(),
+            "../index.html"
+        );
+    }
+
+    for header in [
+        (http::header::IF_NONE_MATCH, "\"other\"".to_string()),
+        (
+            http::header::IF_MODIFIED_SINCE,
+            "Tue, 14 Nov 2023 22:13:19 GMT".to_string(),
+        ),
+    ] {
+        let response = assets
+            .clone()
+            .oneshot(request("/deep/link", Some(header.clone())))
+            .await?;
+        assert_eq!(response.status(), StatusCode::OK, "{:?}", header);
+    }
+
+    // a fallback response with 404 is never revalidated
+    let etag_404 = hash_to_string(&Asset::new("not found").metadata.sha256_hash);
+    let response = assets
+        .clone()
+        .oneshot(request(
+            "/docs/missing",
+            Some((http::header::IF_NONE_MATCH, etag_404)),
+        ))
+        .await?;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert!(response
+        .headers()
+        .get(http::header::CONTENT_LOCATION)
+        .is_none());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_error_negotiation() -> anyhow::Result<()> {
+    let assets = ServeEmbed::<Assets>::new();
+    let browser = "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8";
+
+    for (method, uri, accept, status, content_type, body) in [
+        ("GET", "/missing", None, StatusCode::NOT_FOUND, "text/html", &include_bytes!("assets/404.html")[..]),
+        ("GET", "/missing", Some("*/*"), StatusCode::NOT_FOUND, "text/html", include_bytes!("assets/404.html")),
+        ("GET", "/missing", Some(browser), StatusCode::NOT_FOUND, "text/html", include_bytes!("assets/404.html")),
+        (
+            "GET",
+            "/missing\"",
+            Some("application/json"),
+            StatusCode::NOT_FOUND,
+            "application/problem+json",
+            br#"{"type":"about:blank","title":"Not Found","status":404,"detail":"The requested URL was not found on this server.","instance":"/missing\""}"#,
+        ),
+        ("GET", "/missing", Some("text/plain, */*;q=0.1"), StatusCode::NOT_FOUND, "text/plain", b"Not found"),
+        ("POST", "/", None, StatusCode::METHOD_NOT_ALLOWED, "text/plain", b"Method not allowed"),
+        (
+            "POST",
+            "/",
+            Some("application/problem+json"),
+            StatusCode::METHOD_NOT_ALLOWED,
+            "application/problem+json",
+            br#"{"type":"about:blank","title":"Method Not Allowed","status":405,"detail":"The requested method is not allowed for the URL.","instance":"/"}"#,
+        ),
+        ("GET", "/subdir", None, StatusCode::MOVED_PERMANENTLY, "text/plain", b"Moved permanently"),
+        (
+            "GET",
+            "/subdir",
+            Some(browser),
+            StatusCode::MOVED_PERMANENTLY,
+            "text/html",
+            b"<!DOCTYPE html>\n<html>\n\n<head>\n    <title>301 Moved Permanently</title>\n</head>\n\n<body>\n    <h1>Moved Permanently</h1>\n    <p>The document has moved <a href=\"subdir/\">here</a>.</p>\n</body>\n\n</html>",
+        ),
+        ("GET", "/subdir", Some("application/json"), StatusCode::MOVED_PERMANENTLY, "text/plain", b"Moved permanently"),
+    ] {
+        let mut request = Request::builder().method(method).uri(uri);
+        if let Some(accept) = accept {
+            request = request.header(http::header::ACCEPT, accept);
+        }
+        let response = assets
+            .clone()
+            .oneshot(request.body(Body::empty())?)
+            .await?;
+        assert_eq!(response.status(), status, "{} {} {:?}", method, uri, accept);
+        assert_eq!(
+            response.headers().get(http::header::CONTENT_TYPE).unwrap(),
+            content_type,
+            "{} {} {:?}",
+            method,
+            uri,
+            accept
+        );
+        let data = response.into_body().collect().await?.to_bytes();
+        assert_eq!(&data[..], body, "{} {} {:?}", method, uri, accept);
+    }
+
+    // registered pages are served for their status code and format
+    let source: MemorySource = [
+        ("errors/404.html", "<h1>custom</h1>"),
+        ("errors/404.json", r#"{"title":"custom"}"#),
+    ]
+    .into_iter()
+    .collect();
+    let assets = ServeEmbed::builder_from_source(source)
+        .error_page(StatusCode::NOT_FOUND, ErrorFormat::Html, "errors/404.html")
+        .error_page(
+            StatusCode::NOT_FOUND,
+            ErrorFormat::ProblemJson,
+            "errors/404.json",
+        )
+        .build();
+    for (accept, content_type, body) in [
+        ("text/html", "text/html", &b"<h1>custom</h1>"[..]),
+        (
+            "application/json",
+            "application/problem+json",
+            br#"{"title":"custom"}"#,
+        ),
+        ("text/plain", "text/plain", b"Not found"),
+    ] {
+        let response = assets
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/missing")
+                    .header(http::header::ACCEPT, accept)
+                    .body(Body::empty())?,
+            )
+            .await?;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(
+            response.headers().get(http::header::CONTENT_TYPE).unwrap(),
+            content_type
+        );
+        let data = response.into_body().collect().await?.to_bytes();
+        assert_eq!(&data[..], body);
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_error_templates() -> anyhow::Result<()> {
+    let source: MemorySource = [
+        (
+            "404.html",
+            "<p>{{ path }} ({{status}}) {{request_id}} {{unknown}}</p>",
+        ),
+        ("errors/410.json", r#"{"path":"{{path}}"}"#),
+        ("index.html", "{{path}}"),
+    ]
+    .into_iter()
+    .collect();
+    let assets = ServeEmbed::builder_from_source(source)
+        .fallback_file("404.html")
+        .fallback_rule(FallbackRule::prefix(
+            "app",
+            "index.html",
+            FallbackBehavior::Ok,
+        ))
+        .fallback_rule(FallbackRule::prefix(
+            "old",
+            "missing.html",
+            FallbackBehavior::Status(StatusCode::GONE),
+        ))
+        .error_page(
+            StatusCode::GONE,
+            ErrorFormat::ProblemJson,
+            "errors/410.json",
+        )
+        .error_templates(true)
+        .build();
+
+    for (uri, accept, status, cache_control, body) in [
+        (
+            "/a%3Cb%3E",
+            None,
+            StatusCode::NOT_FOUND,
+            Some("no-store"),
+            "<p>/a&lt;b&gt; (404) abc-123 {{unknown}}</p>",
+        ),
+        (
+            "/old/\"x\"",
+            Some("application/json"),
+            StatusCode::GONE,
+            Some("no-store"),
+            r#"{"path":"/old/\"x\""}"#,
+        ),
+        // a fallback file served with 200 is not an error page
+        ("/app/page", None, StatusCode::OK, None, "{{path}}"),
+        ("/index.html", None, StatusCode::OK, None, "{{path}}"),
+    ] {
+        let mut request = Request::builder()
+            .uri(uri)
+            .header("x-request-id", "abc-123");
+        if let Some(accept) = accept {
+            request = request.header(http::header::ACCEPT, accept);
+        }
+        let response = assets.clone().oneshot(request.body(Body::empty())?).await?;
+        assert_eq!(response.status(), status, "{}", uri);
+        assert_eq!(
+            response
+                .headers()
+                .get(http::header::CACHE_CONTROL)
+                .map(|x| x.to_str().unwrap()),
+            cache_control,
+            "{}",
+            uri
+        );
+        assert_eq!(
+            response.headers().get(http::header::ETAG).is_none(),
+            cache_control.is_some(),
+            "{}",
+            uri
+        );
+        let data = response.into_body().collect().await?.to_bytes();
+        assert_eq!(&data[..], body.as_bytes(), "{}", uri);
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_suggestions() -> anyhow::Result<()> {
+    let assets = ServeEmbed::<Assets>::builder().suggestions(true).build();
+
+    for (uri, link) in [
+        ("/Sample.JS", Some("<sample.js>; rel=\"alternate\"")),
+        ("/sampel.js", Some("<sample.js>; rel=\"alternate\"")),
+        ("/style.scss", Some("<style.css>; rel=\"alternate\"")),
+        (
+            "/images/fox/fox.web",
+            Some("<../../images/fox/fox.webp>; rel=\"alternate\""),
+        ),
+        ("/completely/unrelated", None),
+    ] {
+        let response = assets
+            .clone()
+            .oneshot(Request::builder().uri(uri).body(Body::empty())?)
+            .await?;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND, "{}", uri);
+        assert_eq!(
+            response
+                .headers()
+                .get(http::header::LINK)
+                .map(|x| x.to_str().unwrap()),
+            link,
+            "{}",
+            uri
+        );
+    }
+
+    // suggestions are available to error templates
+    let mut source = MemorySource::new();
+    source.insert(
+        "404.html",
+        "Did you mean <a href=\"{{suggestion}}\">{{suggestions}}</a>?",
+    );
+    source.insert("docs/intro.html", "intro");
+    source.insert("docs/intra.html", "intra");
+    source.insert("docs/Report.html", "report");
+    source.insert("docs/report.htm", "old report");
+    let assets = ServeEmbed::builder_from_source(source)
+        .fallback_file("404.html")
+        .error_templates(true)
+        .suggestion_redirect(true)
+        .build();
+    let response = assets
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/docs/intre.html")
+                .body(Body::empty())?,
+        )
+        .await?;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let data = response.into_body().collect().await?.to_bytes();
+    assert_eq!(
+        &data[..],
+        b"Did you mean <a href=\"../docs/intra.html\">../docs/intra.html, ../docs/intro.html</a>?"
+    );
+
+    // a single suggestion with high confidence is redirected
+    for (uri, location) in [
+        ("/docs/INTRO.html", "../docs/intro.html"),
+        ("/docs/intro.htm", "../docs/intro.html"),
+        // a path that differs only in case wins over another extension
+        ("/docs/REPORT.HTML", "../docs/Report.html"),
+    ] {
+        let response = assets
+            .clone()
+            .oneshot(Request::builder().uri(uri).body(Body::empty())?)
+            .await?;
+        assert_eq!(response.status(), StatusCode::TEMPORARY_REDIRECT, "{}", uri);
+        assert_eq!(
+            response.headers().get(http::header::LOCATION).unwrap(),
+            location
+        );
+    }
+
+    // an extension of another MIME type is not redirected
+    let assets = ServeEmbed::<Assets>::builder()
+        .suggestion_redirect(true)
+        .build();
+    let response = assets
+        .oneshot(Request::builder().uri("/style.js").body(Body::empty())?)
+        .await?;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert_eq!(
+        response.headers()[http::header::LINK],
+        "<style.css>; rel=\"alternate\""
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_head_method() -> anyhow::Result<()> {
+    let app = axum::Router::new().fallback_service(ServeEmbed::<Assets>::new());
+    for uri in ["/", "/sample.js", "/style.css", "/subdir", "/missing"] {
+        let request = |method: http::Method| {
+            Request::builder()
+                .method(method)
+                .uri(uri)
+                .header(http::header::ACCEPT_ENCODING, "br, gzip")
+                .body(Body::empty())
+        };
+        let get = app.clone().oneshot(request(http::Method::GET)?).await?;
+        let head = app.clone().oneshot(request(http::Method::HEAD)?).await?;
+        assert_eq!(head.status(), get.status(), "{}", uri);
+        assert_eq!(head.headers(), get.headers(), "{}", uri);
+        let content_length = head.headers()[http::header::CONTENT_LENGTH].clone();
+
+        let get_data = get.into_body().collect().await?.to_bytes();
+        let head_data = head.into_body().collect().await?.to_bytes();
+        assert!(head_data.is_empty(), "{}", uri);
+        assert_eq!(content_length, get_data.len().to_string(), "{}", uri);
+    }
+
+    // 304 has no content length
+    let response = app
+        .clone()
+        .oneshot(Request::builder().uri("/index.html").body(Body::empty())?)
+        .await?;
+    let etag = response.headers()[http::header::ETAG].clone();
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(http::Method::HEAD)
+                .uri("/index.html")
+                .header(http::header::IF_NONE_MATCH, etag)
+                .body(Body::empty())?,
+        )
+        .await?;
+    assert_eq!(response.status(), http::StatusCode::NOT_MODIFIED);
+    assert_eq!(response.headers().get(http::header::CONTENT_LENGTH), None);
+    assert!(response.into_body().collect().await?.to_bytes().is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_methods() -> anyhow::Result<()> {
+    let request = |method: &str, uri: &str| {
+        Request::builder()
+            .method(method)
+            .uri(uri)
+            .body(Body::empty())
+    };
+
+    let app = axum::Router::new().fallback_service(ServeEmbed::<Assets>::new());
+    let response = app
+        .clone()
+        .oneshot(request("OPTIONS", "/style.css")?)
+        .await?;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    assert_eq!(
+        response.headers()[http::header::ALLOW],
+        "GET, HEAD, OPTIONS"
+    );
+    assert_eq!(response.headers().get(http::header::CONTENT_LENGTH), None);
+    assert!(response.into_body().collect().await?.to_bytes().is_empty());
+
+    for method in ["POST", "PUT", "DELETE"] {
+        let response = app.clone().oneshot(request(method, "/index.html")?).await?;
+        assert_eq!(
+            response.status(),
+            StatusCode::METHOD_NOT_ALLOWED,
+            "{}",
+            method
+        );
+        assert_eq!(
+            response.headers()[http::header::ALLOW],
+            "GET, HEAD, OPTIONS"
+        );
+    }
+
+    // POST to the single page application is served as GET
+    let app = axum::Router::new().fallback_service(
+        ServeEmbed::<Assets>::builder()
+            .fallback_file("index.html")
+            .fallback_behavior(FallbackBehavior::Ok)
+            .method(http::Method::POST)
+            .build(),
+    );
+    let response = app.clone().oneshot(request("POST", "/login")?).await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        &response.into_body().collect().await?.to_bytes()[..],
+        include_bytes!("../examples/assets/index.html")
+    );
+    let response = app.clone().oneshot(request("PUT", "/login")?).await?;
+    assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+    assert_eq!(
+        response.headers()[http::header::ALLOW],
+        "GET, HEAD, POST, OPTIONS"
+    );
+    let response = app.oneshot(request("OPTIONS", "/")?).await?;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    assert_eq!(
+        response.headers()[http::header::ALLOW],
+        "GET, HEAD, POST, OPTIONS"
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_cors() -> anyhow::Result<()> {
+    let app = axum::Router::new().fallback_service(
+        ServeEmbed::<Assets>::builder()
+            .cors_rule(CorsRule::any_origin("*.css").expose_header(http::header::ETAG))
+            .cors_rule(CorsRule::any_origin("images/**").allow_credentials(true))
+            .cors_rule(
+                CorsRule::origins("**/*.js", ["https://app.example.com"])
+                    .allow_credentials(true)
+                    .allow_header(http::header::RANGE)
+                    .max_age(600),
+            )
+            .build(),
+    );
+    let request = |method: &str, uri: &str, origin: Option<&str>| {
+        let mut request = Request::builder().method(method).uri(uri);
+        if let Some(origin) = origin {
+            request = request.header(http::header::ORIGIN, origin);
+        }
+        request.body(Body::empty())
+    };
+    let header = |headers: &http::HeaderMap, name: http::HeaderName| {
+        headers
+            .get_all(name)
+            .iter()
+            .map(|x| x.to_str().unwrap().to_owned())
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    // any origin
+    let response = app
+        .clone()
+        .oneshot(request("GET", "/style.css", Some("https://a.example.com"))?)
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        header(
+            response.headers(),
+            http::header::ACCESS_CONTROL_ALLOW_ORIGIN
+        ),
+        "*"
+    );
+    assert_eq!(
+        header(
+            response.headers(),
+            http::header::ACCESS_CONTROL_EXPOSE_HEADERS
+        ),
+        "etag"
+    );
+    assert_eq!(header(response.headers(), http::header::VARY), "Origin");
+    assert_eq!(
+        header(
+            response.headers(),
+            http::header::ACCESS_CONTROL_ALLOW_CREDENTIALS
+        ),
+        ""
+    );
+
+    // listed origin with credentials
+    let response = app
+        .clone()
+        .oneshot(request(
+            "GET",
+            "/sample.js",
+            Some("https://APP.example.com"),
+        )?)
+        .await?;
+    assert_eq!(
+        header(
+            response.headers(),
+            http::header::ACCESS_CONTROL_ALLOW_ORIGIN
+        ),
+        "https://APP.example.com"
+    );
+    assert_eq!(
+        header(
+            response.headers(),
+            http::header::ACCESS_CONTROL_ALLOW_CREDENTIALS
+        ),
+        "true"
+    );
+    assert_eq!(header(response.headers(), http::header::VARY), "Origin");
+
+    // origin not listed
+    let response = app
+        .clone()
+        .oneshot(request(
+            "GET",
+            "/sample.js",
+            Some("https://evil.example.com"),
+        )?)
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        header(
+            response.headers(),
+            http::header::ACCESS_CONTROL_ALLOW_ORIGIN
+        ),
+        ""
+    );
+    assert_eq!(header(response.headers(), http::header::VARY), "Origin");
+
+    // credentials are not allowed for any origin
+    let response = app
+        .clone()
+        .oneshot(request(
+            "GET",
+            "/images/fox/fox.webp",
+            Some("https://evil.example.com"),
+        )?)
+        .await?;
+    assert_eq!(
+        header(
+            response.headers(),
+            http::header::ACCESS_CONTROL_ALLOW_ORIGIN
+        ),
+        "*"
+    );
+    assert_eq!(
+        header(
+            response.headers(),
+            http::header::ACCESS_CONTROL_ALLOW_CREDENTIALS
+        ),
+        ""
+    );
+
+    // no rule
+    let response = app
+        .clone()
+        .oneshot(request(
+            "GET",
+            "/index.html",
+            Some("https://app.example.com"),
+        )?)
+        .await?;
+    assert_eq!(
+        header(
+            response.headers(),
+            http::header::ACCESS_CONTROL_ALLOW_ORIGIN
+        ),
+        ""
+    );
+    assert_eq!(header(response.headers(), http::header::VARY), "");
+
+    // preflight
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("OPTIONS")
+                .uri("/sample.js")
+                .header(http::header::ORIGIN, "https://app.example.com")
+                .header(http::header::ACCESS_CONTROL_REQUEST_METHOD, "GET")
+                .header(http::header::ACCESS_CONTROL_REQUEST_HEADERS, "range")
+                .body(Body::empty())?,
+        )
+        .await?;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    assert_eq!(
+        header(
+            response.headers(),
+            http::header::ACCESS_CONTROL_ALLOW_ORIGIN
+        ),
+        "https://app.example.com"
+    );
+    assert_eq!(
+        header(
+            response.headers(),
+            http::header::ACCESS_CONTROL_ALLOW_METHODS
+        ),
+        "GET, HEAD, OPTIONS"
+    );
+    assert_eq!(
+        header(
+            response.headers(),
+            http::header::ACCESS_CONTROL_ALLOW_HEADERS
+        ),
+        "range"
+    );
+    assert_eq!(
+        header(response.headers(), http::header::ACCESS_CONTROL_MAX_AGE),
+        "600"
+    );
+    assert_eq!(
+        header(
+            response.headers(),
+            http::header::ACCESS_CONTROL_ALLOW_CREDENTIALS
+        ),
+        "true"
+    );
+
+    // preflight from an origin that is not listed
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("OPTIONS")
+                .uri("/sample.js")
+                .header(http::header::ORIGIN, "https://evil.example.com")
+                .header(http::header::ACCESS_CONTROL_REQUEST_METHOD, "GET")
+                .body(Body::empty())?,
+        )
+        .await?;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    assert_eq!(
+        header(
+            response.headers(),
+            http::header::ACCESS_CONTROL_ALLOW_ORIGIN
+        ),
+        ""
+    );
+    assert_eq!(
+        header(
+            response.headers(),
+            http::header::ACCESS_CONTROL_ALLOW_METHODS
+        ),
+        ""
+    );
+
+    // preflight is answered before the fallback service
+    let inner = tower::service_fn(|_: Request<Body>| async {
+        Ok::<_, std::convert::Infallible>(
+            http::Response::builder()
+                .status(StatusCode::IM_A_TEAPOT)
+                .body(Body::empty())
+                .unwrap(),
+        )
+    });
+    let app = ServeEmbed::<Assets>::builder()
+        .cors_rule(CorsRule::any_origin("fonts/**"))
+        .build()
+        .fallback_service(inner);
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("OPTIONS")
+                .uri("/fonts/a.woff2")
+                .header(http::header::ORIGIN, "https://app.example.com")
+                .header(http::header::ACCESS_CONTROL_REQUEST_METHOD, "GET")
+                .body(Body::empty())?,
+        )
+        .await?;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    assert_eq!(
+        header(
+            response.headers(),
+            http::header::ACCESS_CONTROL_ALLOW_ORIGIN
+        ),
+        "*"
+    );
+    let response = app.oneshot(request("OPTIONS", "/api", None)?).await?;
+    assert_eq!(response.status(), StatusCode::IM_A_TEAPOT);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_cross_origin_isolation() -> anyhow::Result<()> {
+    let app = axum::Router::new().fallback_service(
+        ServeEmbed::<Assets>::builder()
+            .cross_origin_isolation(true)
+            .build(),
+    );
+    for (uri, coop, coep) in [
+        ("/", Some("same-origin"), Some("require-corp")),
+        ("/missing", Some("same-origin"), Some("require-corp")),
+        ("/sample.js", None, Some("require-corp")),
+        ("/style.css", None, None),
+        ("/images/fox/fox.webp", None, None),
+    ] {
+        let response = app
+            .clone()
+            .oneshot(Request::builder().uri(uri).body(Body::empty())?)
+            .await?;
+        let header = |name: &str| {
+            response
+                .headers()
+                .get(name)
+                .map(|x| x.to_str().unwrap().to_owned())
+        };
+        assert_eq!(
+            header("cross-origin-opener-policy").as_deref(),
+            coop,
+            "{}",
+            uri
+        );
+        assert_eq!(
+            header("cross-origin-embedder-policy").as_deref(),
+            coep,
+            "{}",
+            uri
+        );
+        assert_eq!(
+            header("cross-origin-resource-policy").as_deref(),
+            Some("same-origin"),
+            "{}",
+            uri
+        );
+    }
+
+    // disabled by default
+    let response = ServeEmbed::<Assets>::new()
+        .oneshot(Request::builder().uri("/").body(Body::empty())?)
+        .await?;
+    assert!(!response
+        .headers()
+        .contains_key("cross-origin-opener-policy"));
+    assert!(!response
+        .headers()
+        .contains_key("cross-origin-resource-policy"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_security_headers() -> anyhow::Result<()> {
+    // nosniff by default
+    let response = ServeEmbed::<Assets>::new()
+        .oneshot(Request::builder().uri("/style.css").body(Body::empty())?)
+        .await?;
+    assert_eq!(
+        response.headers()[http::header::X_CONTENT_TYPE_OPTIONS],
+        "nosniff"
+    );
+    assert!(!response
+        .headers()
+        .contains_key(http::header::CONTENT_SECURITY_POLICY));
+
+    let app = axum::Router::new().fallback_service(
+        ServeEmbed::<Assets>::builder()
+            .security_headers(
+                SecurityHeaders::new()
+                    .content_security_policy(http::HeaderValue::from_static("default-src 'self'"))
+                    .referrer_policy(http::HeaderValue::from_static("no-referrer"))
+                    .permissions_policy(http::HeaderValue::from_static("camera=()"))
+                    .frame_options(http::HeaderValue::from_static("DENY")),
+            )
+            .security_headers_for(
+                "subdir/**",
+                SecurityHeaders::new()
+                    .nosniff(false)
+                    .frame_options(http::HeaderValue::from_static("SAMEORIGIN")),
+            )
+            .build(),
+    );
+    for (uri, nosniff, csp, referrer, permissions, frame) in [
+        (
+            "/",
+            Some("nosniff"),
+            Some("default-src 'self'"),
+            Some("no-referrer"),
+            Some("camera=()"),
+            Some("DENY"),
+        ),
+        (
+            "/missing",
+            Some("nosniff"),
+            Some("default-src 'self'"),
+            Some("no-referrer"),
+            Some("camera=()"),
+            Some("DENY"),
+        ),
+        ("/sample.js", Some("nosniff"), None, None, None, None),
+        ("/subdir/", None, None, None, None, Some("SAMEORIGIN")),
+    ] {
+        let response = app
+            .clone()
+            .oneshot(Request::builder().uri(uri).body(Body::empty())?)
+            .await?;
+        let header = |name: &str| {
+            response
+                .headers()
+                .get(name)
+                .map(|x| x.to_str().unwrap().to_owned())
+        };
+        assert_eq!(
+            header("x-content-type-options").as_deref(),
+            nosniff,
+            "{}",
+            uri
+        );
+        assert_eq!(header("content-security-policy").as_deref(), csp, "{}", uri);
+        assert_eq!(header("referrer-policy").as_deref(), referrer, "{}", uri);
+        assert_eq!(
+            header("permissions-policy").as_deref(),
+            permissions,
+            "{}",
+            uri
+        );
+        assert_eq!(header("x-frame-options").as_deref(), frame, "{}", uri);
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_csp_nonce() -> anyhow::Result<()> {
+    let mut source = MemorySource::new();
+    source
+        .insert(
+            "index.html",
+            "<script>run()</script><STYLE>p{}</STYLE><script\nsrc=\"app.js\"></script><scripts>",
+        )
+        .insert(
+            "page.html",
+            "<script nonce=\"{{csp_nonce}}\">run()</script>",
+        )
+        .insert(
+            "inline.html",
+            "<script>var s='<script>';</script><!-- <script> --><script nonce=\"abc\">run()</script><style>p{}</style>",
+        )
+        .insert("app.js", "document.write('<script>');");
+    let security_headers = SecurityHeaders::new().content_security_policy(
+        http::HeaderValue::from_static("script-src 'nonce-{{csp_nonce}}'"),
+    );
+    let nonce_of = |response: &http::Response<ServeBody>| {
+        let csp = response.headers()[http::header::CONTENT_SECURITY_POLICY]
+            .to_str()
+            .unwrap();
+        csp.strip_prefix("script-src 'nonce-")
+            .and_then(|x| x.strip_suffix('\''))
+            .unwrap()
+            .to_owned()
+    };
+
+    // every tag
+    let app = ServeEmbed::builder_from_source(source.clone())
+        .security_headers(security_headers.clone())
+        .csp_nonce(CspNonce::Tags)
+        .build();
+    let mut nonces = Vec::new();
+    for _ in 0..2 {
+        let response = app
+            .clone()
+            .oneshot(Request::builder().uri("/").body(Body::empty())?)
+            .await?;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers().get(http::header::ETAG), None);
+        assert_eq!(response.headers()[http::header::CACHE_CONTROL], "no-store");
+        let nonce = nonce_of(&response);
+        assert_eq!(nonce.len(), 32);
+        let body = response.into_body().collect().await?.to_bytes();
+        assert_eq!(
+            body,
+            format!(
+                "<script nonce=\"{0}\">run()</script><STYLE nonce=\"{0}\">p{{}}</STYLE><script nonce=\"{0}\"\nsrc=\"app.js\"></script><scripts>",
+                nonce
+            )
+        );
+        nonces.push(nonce);
+    }
+    assert_ne!(nonces[0], nonces[1]);
+
+    // If-None-Match does not return 304 for a rewritten file
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/")
+                .header(http::header::IF_NONE_MATCH, "*")
+                .body(Body::empty())?,
+        )
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // script content, comments and tags with a nonce are not rewritten
+    let response = app
+        .clone()
+        .oneshot(Request::builder().uri("/inline.html").body(Body::empty())?)
+        .await?;
+    let nonce = nonce_of(&response);
+    assert_eq!(
+        response.into_body().collect().await?.to_bytes(),
+        format!(
+            "<script nonce=\"{0}\">var s='<script>';</script><!-- <script> --><script nonce=\"abc\">run()</script><style nonce=\"{0}\">p{{}}</style>",
+            nonce
+        )
+    );
+
+    // other files are not rewritten
+    let response = app
+        .oneshot(Request::builder().uri("/app.js").body(Body::empty())?)
+        .await?;
+    assert!(response.headers().contains_key(http::header::ETAG));
+    assert_eq!(
+        response.into_body().collect().await?.to_bytes(),
+        "document.write('<script>');"
+    );
+
+    // placeholder
+    let app = ServeEmbed::builder_from_source(source.clone())
+        .security_headers(security_headers.clone())
+        .csp_nonce(CspNonce::Placeholder)
+        .build();
+    let response = app
+        .clone()
+        .oneshot(Request::builder().uri("/page.html").body(Body::empty())?)
+        .await?;
+    let nonce = nonce_of(&response);
+    assert_eq!(
+        response.into_body().collect().await?.to_bytes(),
+        format!("<script nonce=\"{}\">run()</script>", nonce)
+    );
+    let response = app
+        .oneshot(Request::builder().uri("/").body(Body::empty())?)
+        .await?;
+    assert!(response.headers().contains_key(http::header::ETAG));
+    assert_eq!(response.headers().get(http::header::CACHE_CONTROL), None);
+
+    // error pages
+    source.insert("err.html", "<script>run()</script>");
+    let app = ServeEmbed::builder_from_source(source)
+        .security_headers(security_headers)
+        .csp_nonce(CspNonce::Tags)
+        .error_page(StatusCode::NOT_FOUND, ErrorFormat::Html, "err.html")
+        .build();
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/missing")
+                .header(http::header::ACCEPT, "text/html")
+                .body(Body::empty())?,
+        )
+        .await?;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert_eq!(response.headers()[http::header::CACHE_CONTROL], "no-store");
+    let nonce = nonce_of(&response);
+    assert_eq!(
+        response.into_body().collect().await?.to_bytes(),
+        format!("<script nonce=\"{}\">run()</script>", nonce)
+    );
+
+    Ok(())
+}
+
+#[cfg(feature = "runtime-config")]
+#[tokio::test]
+async fn test_runtime_config() -> anyhow::Result<()> {
+    let mut source = MemorySource::new();
+    source
+        .insert("index.html", "<html><HEAD><title>app</title></HEAD></html>")
+        .insert("marker.html", "<body><!-- runtime-config --></body>")
+        .insert("plain.html", "<p>no head</p>")
+        .insert("doctype.html", "<!DOCTYPE html>\n<body>hi</body>")
+        .insert("app.js", "</head>");
+    let get = |app: ServeEmbed<MemorySource>, uri: &'static str| async move {
+        let response = app
+            .oneshot(Request::builder().uri(uri).body(Body::empty())?)
+            .await?;
+        let etag = response.headers()[http::header::ETAG].clone();
+        let data = response.into_body().collect().await?.to_bytes();
+        anyhow::Ok((etag, data))
+    };
+
+    let app = ServeEmbed::builder_from_source(source.clone())
+        .runtime_config(serde_json::json!({
+            "apiUrl": "https://api.example.com",
+            "title": "</script><script>alert(1)</script>",
+            "flags": { "beta": true },
+        }))
+        .build();
+    let (etag, data) = get(app.clone(), "/").await?;
+    assert_eq!(
+        data,
+        r#"<html><HEAD><title>app</title><script>window.__CONFIG__={"apiUrl":"https://api.example.com","flags":{"beta":true},"title":"\u003c/script\u003e\u003cscript\u003ealert(1)\u003c/script\u003e"};</script></HEAD></html>"#
+    );
+    assert_eq!(
+        etag,
+        hash_to_string(&Asset::new(data.clone()).metadata.sha256_hash)
+    );
+
+    let (_, data) = get(app.clone(), "/marker.html").await?;
+    assert!(data.starts_with(b"<body><script>window.__CONFIG__={"));
+    assert!(data.ends_with(b"};</script></body>"));
+    let (_, data) = get(app.clone(), "/plain.html").await?;
+    assert!(data.starts_with(b"<script>window.__CONFIG__={"));
+    assert!(data.ends_with(b"};</script><p>no head</p>"));
+    let (_, data) = get(app.clone(), "/doctype.html").await?;
+    assert!(data.starts_with(b"<!DOCTYPE html><script>window.__CONFIG__={"));
+    assert!(data.ends_with(b"};</script>\n<body>hi</body>"));
+    let (_, data) = get(app, "/app.js").await?;
+    assert_eq!(data, "</head>");
+
+    // a closure is called for each request, and the ETag follows the value
+    let counter = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let app = ServeEmbed::builder_from_source(source.clone())
+        .runtime_config_with({
+            let counter = counter.clone();
+            move || counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst)
+        })
+        .build();
+    let (etag1, data) = get(app.clone(), "/marker.html").await?;
+    assert_eq!(data, "<body><script>window.__CONFIG__=0;</script></body>");
+    let (etag2, data) = get(app, "/marker.html").await?;
+    assert_eq!(data, "<body><script>window.__CONFIG__=1;</script></body>");
+    assert_ne!(etag1, etag2);
+
+    // the script has the nonce of the request
+    for mode in [CspNonce::Placeholder, CspNonce::Tags] {
+        let app = ServeEmbed::builder_from_source(source.clone())
+            .runtime_config(1)
+            .security_headers(SecurityHeaders::new().content_security_policy(
+                http::HeaderValue::from_static("script-src 'nonce-{{csp_nonce}}'"),
+            ))
+            .csp_nonce(mode)
+            .build();
+        let response = app
+            .oneshot(Request::builder().uri("/marker.html").body(Body::empty())?)
+            .await?;
+        let csp = response.headers()[http::header::CONTENT_SECURITY_POLICY]
+            .to_str()?
+            .to_owned();
+        let nonce = csp
+            .strip_prefix("script-src 'nonce-")
+            .and_then(|x| x.strip_suffix('\''))
+            .unwrap();
+        let data = response.into_body().collect().await?.to_bytes();
+        assert_eq!(
+            data,
+            format!(
+                "<body><script nonce=\"{}\">window.__CONFIG__=1;</script></body>",
+                nonce
+            ),
+            "{:?}",
+            mode
+        );
+    }
+
+    // error pages get the configuration with the nonce
+    source.insert("err.html", "<head></head>missing");
+    let app = ServeEmbed::builder_from_source(source)
+        .runtime_config(1)
+        .security_headers(SecurityHeaders::new().content_security_policy(
+            http::HeaderValue::from_static("script-src 'nonce-{{csp_nonce}}'"),
+        ))
+        .csp_nonce(CspNonce::Placeholder)
+        .error_page(StatusCode::NOT_FOUND, ErrorFormat::Html, "err.html")
+        .build();
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/missing")
+                .header(http::header::ACCEPT, "text/html")
+                .body(Body::empty())?,
+        )
+        .await?;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let csp = response.headers()[http::header::CONTENT_SECURITY_POLICY]
+        .to_str()?
+        .to_owned();
+    let data = response.into_body().collect().await?.to_bytes();
+    assert_eq!(
+        data,
+        format!(
+            "<head><script nonce=\"{}\">window.__CONFIG__=1;</script></head>missing",
+            csp.strip_prefix("script-src 'nonce-")
+                .and_then(|x| x.strip_suffix('\''))
+                .unwrap()
+        )
+    );
+    Ok(())
+}
@@ -0,0 +1,165 @@
+use std::{
+    convert::Infallible,
+    sync::{Arc, RwLock},
+    task::Poll,
+};
+
+use http::Request;
+use tower_service::Service;
+
+use crate::{AssetSource, Bundle, MemorySource, ServeBody, ServeEmbed, ServeFuture};
+
+/// [`VirtualHosts`] is a service that dispatches a request to a [`ServeEmbed`] by the host name of the request.
+///
+/// The host name is taken from the `Host` header, or from the authority of the request URI (the `:authority` pseudo header of HTTP/2).
+/// The port and a trailing dot are ignored, and host names are compared case-insensitively.
+///
+/// A pattern is either an exact host name, such as `"docs.example.com"`, or a wildcard, such as `"*.example.com"`.
+/// A wildcard matches any subdomain, but not the domain itself. An exact host name wins over a wildcard,
+/// and a longer wildcard wins over a shorter one.
+/// If no pattern matches, the request is served by the default service, or the default 404 page is served if there is no default service.
+///
+/// Each host is served by its own [`ServeEmbed`], so the index file, the fallback file and the other settings can differ per host.
+///
+/// # Example
+/// ```ignore
+/// let app = VirtualHosts::new()
+///     .host("docs.example.com", ServeEmbed::<Docs>::new())
+///     .host(
+///         "*.app.example.com",
+///         ServeEmbed::<App>::builder()
+///             .fallback_file("index.html")
+///             .fallback_behavior(FallbackBehavior::Ok)
+///             .build(),
+///     )
+///     .default_host(ServeEmbed::<Landing>::new());
+/// let app = axum::Router::new().fallback_service(app);
+/// ```
+#[derive(Debug, Clone)]
+pub struct VirtualHosts {
+    exact: Vec<(String, Route)>,
+    wildcard: Vec<(String, Route)>,
+    default: Route,
+}
+
+/// The bundle of a [`ServeEmbed`], without its type parameter. A swap by [`BundleHandle`](crate::BundleHandle) is reflected.
+#[derive(Clone)]
+struct Route {
+    bundle: Arc<RwLock<Bundle>>,
+}
+
+impl std::fmt::Debug for Route {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Route")
+            .field("config", &self.bundle.read().unwrap().config)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<E: AssetSource> From<ServeEmbed<E>> for Route {
+    fn from(service: ServeEmbed<E>) -> Self {
+        Self {
+            bundle: service.bundle,
+        }
+    }
+}
+
+impl Default for VirtualHosts {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl VirtualHosts {
+    /// Creates a `VirtualHosts` without any host. The default 404 page is served for every request.
+    pub fn new() -> Self {
+        Self {
+            exact: Vec::new(),
+            wildcard: Vec::new(),
+            default: ServeEmbed::from_source(MemorySource::new()).into(),
+        }
+    }
+
+    /// Serves the requests for the hosts that match `pattern` with `service`.
+    ///
+    /// `pattern` is an exact host name, such as `"docs.example.com"`, or a wildcard, such as `"*.example.com"`.
+    /// If the same pattern is added twice, the first one is used.
+    pub fn host<E: AssetSource>(
+        mut self,
+        pattern: impl AsRef<str>,
+        service: ServeEmbed<E>,
+    ) -> Self {
+        let pattern = normalize_host(pattern.as_ref());
+        match pattern.strip_prefix("*.") {
+            Some(suffix) => {
+                self.wildcard.push((format!(".{}", suffix), service.into()));
+                // try longer suffixes first so that the most specific wildcard wins
+                self.wildcard
+                    .sort_by_key(|(x, _)| std::cmp::Reverse(x.len()));
+            }
+            None => self.exact.push((pattern, service.into())),
+        }
+        self
+    }
+
+    /// Serves the requests that do not match any host with `service`.
+    pub fn default_host<E: AssetSource>(mut self, service: ServeEmbed<E>) -> Self {
+        self.default = service.into();
+        self
+    }
+
+    /// Returns the route for `host`, or the default route if no pattern matches.
+    fn route(&self, host: Option<&str>) -> &Route {
+        let Some(host) = host.map(normalize_host) else {
+            return &self.default;
+        };
+        self.exact
+            .iter()
+            .find(|(x, _)| *x == host)
+            .or_else(|| {
+                self.wildcard
+                    .iter()
+                    .find(|(x, _)| host.ends_with(x.as_str()))
+            })
+            .map(|(_, route)| route)
+            .unwrap_or(&self.default)
+    }
+}
+
+/// Returns the host name of a request without the port.
+fn request_host<T>(request: &Request<T>) -> Option<&str> {
+    let host = request
+        .headers()
+        .get(http::header::HOST)
+        .and_then(|x| x.to_str().ok())
+        .or_else(|| request.uri().authority().map(|x| x.as_str()))?;
+    let host = host.rsplit('@').next().unwrap_or(host);
+    if host.starts_with('[') {
+        // IPv6 address, such as `[::1]:8080`
+        host.find(']').map(|x| &host[..=x])
+    } else {
+        host.split(':').next()
+    }
+}
+
+fn normalize_host(host: &str) -> String {
+    host.trim().trim_end_matches('.').to_ascii_lowercase()
+}
+
+impl<T: Send + 'static> Service<Request<T>> for VirtualHosts {
+    type Response = http::Response<ServeBody>;
+    type Error = Infallible;
+    type Future = ServeFuture<VirtualHosts, T>;
+
+    fn poll_ready(
+        &mut self,
+        _cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: Request<T>) -> Self::Future {
+        let route = self.route(request_host(&req));
+        ServeFuture::new(&route.bundle, req)
+    }
+}
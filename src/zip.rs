@@ -0,0 +1,167 @@
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    io::{Read, Seek},
+    path::Path,
+};
+
+use bytes::Bytes;
+use chrono::NaiveDate;
+
+use crate::{Asset, AssetSource, CompressionMethod};
+
+/// [`ZipSource`] is an [`AssetSource`] that serves the entries of a zip archive. This type is available with the `zip` feature.
+///
+/// The archive is loaded at runtime, so the served files can be replaced without rebuilding the server.
+/// All entries are read into memory when the archive is loaded.
+///
+/// Sidecar compressed files in the archive, such as `app.js.br`, are served in the same way as embedded files.
+/// In addition, an entry stored with the deflate method is served to clients that accept `gzip` or `deflate`
+/// by wrapping the compressed data of the entry, without compressing it again.
+///
+/// # Example
+/// ```ignore
+/// let source = ZipSource::open("ui-bundle.zip")?;
+/// let serve_bundle = ServeEmbed::from_source(source);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ZipSource {
+    entries: HashMap<String, ZipEntry>,
+}
+
+#[derive(Debug, Clone)]
+struct ZipEntry {
+    asset: Asset,
+    gzip: Option<Asset>,
+    zlib: Option<Asset>,
+}
+
+impl ZipSource {
+    /// Loads a zip archive from a file.
+    pub fn open(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        Self::from_reader(std::fs::File::open(path)?)
+    }
+
+    /// Loads a zip archive from static data, such as the output of `include_bytes!`.
+    pub fn from_static(data: &'static [u8]) -> std::io::Result<Self> {
+        Self::from_reader(std::io::Cursor::new(data))
+    }
+
+    /// Loads a zip archive from a reader.
+    pub fn from_reader<R: Read + Seek>(reader: R) -> std::io::Result<Self> {
+        let mut archive = zip::ZipArchive::new(reader)?;
+        let mut entries = HashMap::new();
+
+        for index in 0..archive.len() {
+            let mut file = archive.by_index(index)?;
+            if !file.is_file() || file.enclosed_name().is_none() {
+                continue;
+            }
+            let name = file.name().to_owned();
+            let crc32 = file.crc32();
+            let deflated = file.compression() == zip::CompressionMethod::Deflated;
+            let last_modified = file.last_modified().and_then(|x| {
+                NaiveDate::from_ymd_opt(x.year().into(), x.month().into(), x.day().into())?
+                    .and_hms_opt(x.hour().into(), x.minute().into(), x.second().into())
+                    .map(|x| x.and_utc().timestamp() as u64)
+            });
+            let mut data = Vec::with_capacity(file.size() as usize);
+            file.read_to_end(&mut data)?;
+            drop(file);
+
+            let (gzip, zlib) = if deflated {
+                let mut raw = Vec::new();
+                archive.by_index_raw(index)?.read_to_end(&mut raw)?;
+                (
+                    Some(with_last_modified(
+                        Asset::new(gzip_member(&raw, crc32, data.len())),
+                        last_modified,
+                    )),
+                    Some(with_last_modified(
+                        Asset::new(zlib_stream(&raw, adler32(&data))),
+                        last_modified,
+                    )),
+                )
+            } else {
+                (None, None)
+            };
+
+            entries.insert(
+                name,
+                ZipEntry {
+                    asset: with_last_modified(Asset::new(Bytes::from(data)), last_modified),
+                    gzip,
+                    zlib,
+                },
+            );
+        }
+
+        Ok(Self { entries })
+    }
+}
+
+impl AssetSource for ZipSource {
+    fn get(&self, path: &str) -> Option<Asset> {
+        self.entries.get(path).map(|x| x.asset.clone())
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = Cow<'static, str>> + '_> {
+        Box::new(self.entries.keys().map(|x| Cow::Owned(x.clone())))
+    }
+
+    fn get_compressed(&self, path: &str, method: CompressionMethod) -> Option<Asset> {
+        if let Some(x) = self.get(&format!("{}{}", path, method.extension())) {
+            return Some(x);
+        }
+        let entry = self.entries.get(path)?;
+        match method {
+            CompressionMethod::Gzip => entry.gzip.clone(),
+            CompressionMethod::Zlib => entry.zlib.clone(),
+            _ => None,
+        }
+    }
+}
+
+fn with_last_modified(asset: Asset, last_modified: Option<u64>) -> Asset {
+    match last_modified {
+        Some(last_modified) => asset.with_last_modified(last_modified),
+        None => asset,
+    }
+}
+
+/// Wraps raw deflate data into a gzip member (RFC 1952).
+fn gzip_member(raw: &[u8], crc32: u32, size: usize) -> Vec<u8> {
+    let mut data = Vec::with_capacity(raw.len() + 18);
+    data.extend_from_slice(&[0x1f, 0x8b, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xff]);
+    data.extend_from_slice(raw);
+    data.extend_from_slice(&crc32.to_le_bytes());
+    data.extend_from_slice(&(size as u32).to_le_bytes());
+    data
+}
+
+/// Wraps raw deflate data into a zlib stream (RFC 1950).
+fn zlib_stream(raw: &[u8], adler32: u32) -> Vec<u8> {
+    let mut data = Vec::with_capacity(raw.len() + 6);
+    data.extend_from_slice(&[0x78, 0x9c]);
+    data.extend_from_slice(raw);
+    data.extend_from_slice(&adler32.to_be_bytes());
+    data
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    const MOD_ADLER: u32 = 65521;
+    // the largest number of bytes that can be summed before `b` overflows
+    const CHUNK_SIZE: usize = 5552;
+
+    let mut a = 1u32;
+    let mut b = 0u32;
+    for chunk in data.chunks(CHUNK_SIZE) {
+        for byte in chunk {
+            a += u32::from(*byte);
+            b += a;
+        }
+        a %= MOD_ADLER;
+        b %= MOD_ADLER;
+    }
+    (b << 16) | a
+}